@@ -1,5 +1,8 @@
 mkdir -p wasm/build
-cargo build --target wasm32-unknown-unknown --release
+cargo build --target wasm32-unknown-unknown --release "$@"
 cp target/wasm32-unknown-unknown/release/arcade-knight.wasm wasm/build
 cp -r wasm/static/* wasm/build
-cp -r resources wasm/build
+case " $* " in
+    *embedded-assets*) ;; # included in the program
+    *) cp -r resources wasm/build ;;
+esac
@@ -0,0 +1,56 @@
+//! Lists the files of the `resources` directory for the `embedded-assets` feature, which
+//! includes them in the binary.
+
+use std::{
+    env, fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+const RESOURCES: &str = "resources";
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    if env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_none() {
+        return Ok(());
+    }
+    println!("cargo:rerun-if-changed={RESOURCES}");
+
+    let root = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join(RESOURCES);
+    let mut files = Vec::new();
+    list_files(&root, &mut files)?;
+    files.sort();
+
+    let mut entries = String::new();
+    for file in &files {
+        println!("cargo:rerun-if-changed={}", file.display());
+
+        // the files are named like in the asset manifest, relative to the root
+        let name = file.strip_prefix(&root).unwrap().to_string_lossy();
+        let name = name.replace('\\', "/");
+        entries.push_str(&format!(
+            "    ({name:?}, include_bytes!({:?})),\n",
+            file.display().to_string()
+        ));
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    fs::write(
+        out,
+        format!("pub const FILES: &[(&str, &[u8])] = &[\n{entries}];\n"),
+    )
+}
+
+fn list_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -2,11 +2,21 @@ use std::rc::Rc;
 
 use macroquad::prelude::*;
 
-use crate::timers::Timer;
+use crate::{assets::scalable_texture, palette::Palette, resources::Animations, timers::Timer};
 
 pub struct TextureAtlas {
     texture: Texture2D,
-    tiles: Vec<Rect>,
+    tiles: Vec<Tile>,
+}
+
+/// A part of the texture of an atlas.
+#[derive(Clone, Copy)]
+pub struct Tile {
+    /// Rectangle of the tile in the texture.
+    pub source: Rect,
+    /// Point drawn at the position of the tile, relative to the top left corner of the
+    /// source, which may lie outside of it for the trimmed frames.
+    pub pivot: Vec2,
 }
 
 impl TextureAtlas {
@@ -26,85 +36,310 @@ impl TextureAtlas {
         for y in 0..rows {
             for x in 0..columns {
                 let (x, y) = (x as f32, y as f32);
-                tiles.push(Rect::new(x * w, y * h, w, h));
+                tiles.push(Tile {
+                    source: Rect::new(x * w, y * h, w, h),
+                    pivot: vec2(w / 2., h / 2.),
+                });
             }
         }
 
         Self { texture, tiles }
     }
 
-    /// Draws the tile at the given index centered at the given position.
-    pub fn draw_tile_centered(&self, tile_index: usize, x: f32, y: f32) {
+    /// Creates an atlas from tiles placed anywhere in the texture.
+    pub const fn from_tiles(texture: Texture2D, tiles: Vec<Tile>) -> Self {
+        Self { texture, tiles }
+    }
+
+    /// Creates a copy of the atlas with the colors swapped by the palette, from the
+    /// image its texture was created with.
+    pub fn recolored(&self, image: &Image, palette: &Palette) -> Self {
+        let mut image = image.clone();
+        palette.apply(&mut image);
+
+        Self {
+            texture: scalable_texture(&image),
+            tiles: self.tiles.clone(),
+        }
+    }
+
+    /// Returns the number of tiles in the atlas.
+    pub const fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns the size of the tile at the given index, in pixels.
+    pub fn tile_size(&self, tile_index: usize) -> Vec2 {
+        self.tiles[tile_index].source.size()
+    }
+
+    /// Moves the pivot of the tile at the given index.
+    pub fn set_pivot(&mut self, tile_index: usize, pivot: Vec2) {
+        self.tiles[tile_index].pivot = pivot;
+    }
+
+    /// Draws the tile at the given index with its pivot, the center of the grid tiles,
+    /// at the given position.
+    ///
+    /// The flips keep the pivot at the same place in the mirrored tile, and the tile is
+    /// scaled and rotated around it.
+    pub fn draw_tile(&self, tile_index: usize, x: f32, y: f32, options: DrawOptions) {
         let tile = self.tiles.get(tile_index).expect("Tile not found");
-        let (x, y) = (x - tile.w / 2., y - tile.h / 2.);
+        let size = tile.source.size() * options.scale;
 
-        draw_texture_ex(self.texture, x, y, WHITE, draw_tile_params(tile));
+        let mut pivot = tile.pivot * options.scale;
+        if options.flip_x {
+            pivot.x = size.x - pivot.x;
+        }
+        if options.flip_y {
+            pivot.y = size.y - pivot.y;
+        }
+
+        let params = DrawTextureParams {
+            dest_size: Some(size),
+            source: Some(tile.source),
+            rotation: options.rotation,
+            flip_x: options.flip_x,
+            flip_y: options.flip_y,
+            pivot: Some(vec2(x, y)),
+        };
+        draw_texture_ex(self.texture, x - pivot.x, y - pivot.y, options.tint, params);
     }
 }
 
-fn draw_tile_params(tile: &Rect) -> DrawTextureParams {
-    DrawTextureParams {
-        source: Some(*tile),
-        ..DrawTextureParams::default()
+/// How the tiles are drawn.
+#[derive(Clone, Copy)]
+pub struct DrawOptions {
+    /// Whether the tile is mirrored horizontally.
+    pub flip_x: bool,
+    /// Whether the tile is mirrored vertically.
+    pub flip_y: bool,
+    /// Color multiplied with the texture, white keeping its colors.
+    pub tint: Color,
+    /// Clockwise rotation around the pivot, in radians.
+    pub rotation: f32,
+    /// Size multiplier applied around the pivot.
+    pub scale: f32,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        Self {
+            flip_x: false,
+            flip_y: false,
+            tint: WHITE,
+            rotation: 0.,
+            scale: 1.,
+        }
     }
 }
 
-pub struct Animation {
+/// The frames of an animation, shared by all the animations playing it.
+pub struct Clip {
+    /// Name of the clip in the animation manifest.
+    name: String,
     texture_atlas: Rc<TextureAtlas>,
-    /// Indexes of the tiles in the atlas composing the animation.  
+    /// Indexes of the tiles in the atlas composing the animation.
     tile_indexes: Vec<usize>,
-    /// Index of the current frame.
-    pub current_frame: usize,
-    /// Timer keeping track of the frame durations.
-    frame_timer: Timer,
+    /// Duration of each frame, in seconds.
+    frame_durations: Vec<f64>,
+    /// Named events with the index of the frame triggering them.
+    events: Vec<(usize, String)>,
     /// Whether or not the animation is repeating infinitely.
     repeating: bool,
+    /// Whether the frames are mirrored horizontally and vertically, to reuse the frames
+    /// of the opposite direction.
+    flip: (bool, bool),
 }
 
-impl Animation {
-    pub fn new(
+impl Clip {
+    /// Creates a clip, the frames, their durations and the events being already
+    /// validated.
+    pub const fn new(
+        name: String,
         texture_atlas: Rc<TextureAtlas>,
         tile_indexes: Vec<usize>,
-        frame_seconds: f64,
+        frame_durations: Vec<f64>,
+        events: Vec<(usize, String)>,
         repeating: bool,
     ) -> Self {
         Self {
+            name,
             texture_atlas,
             tile_indexes,
+            frame_durations,
+            events,
             repeating,
-            frame_timer: Timer::from_seconds(frame_seconds),
+            flip: (false, false),
+        }
+    }
+
+    /// Mirrors the frames of the clip.
+    pub const fn flipped(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip = (flip_x, flip_y);
+        self
+    }
+
+    /// Returns the atlas of the frames.
+    pub const fn atlas(&self) -> &Rc<TextureAtlas> {
+        &self.texture_atlas
+    }
+
+    /// Creates a copy of the clip under another name, with the same frames taken from
+    /// another atlas.
+    pub fn reskinned(&self, name: String, texture_atlas: Rc<TextureAtlas>) -> Self {
+        Self {
+            name,
+            texture_atlas,
+            tile_indexes: self.tile_indexes.clone(),
+            frame_durations: self.frame_durations.clone(),
+            events: self.events.clone(),
+            repeating: self.repeating,
+            flip: self.flip,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clip {
+    pub fn tile_indexes(&self) -> &[usize] {
+        &self.tile_indexes
+    }
+
+    pub fn frame_durations(&self) -> &[f64] {
+        &self.frame_durations
+    }
+
+    pub fn events(&self) -> &[(usize, String)] {
+        &self.events
+    }
+
+    pub const fn is_repeating(&self) -> bool {
+        self.repeating
+    }
+}
+
+pub struct Animation {
+    clip: Rc<Clip>,
+    /// Index of the current frame.
+    pub current_frame: usize,
+    /// Timer keeping track of the duration of the current frame.
+    frame_timer: Timer,
+    /// Frame entered by the last tick, or at the start, whose events are triggered.
+    entered_frame: Option<usize>,
+}
+
+impl Animation {
+    pub fn new(clip: Rc<Clip>) -> Self {
+        Self {
+            frame_timer: Timer::from_seconds(clip.frame_durations[0]),
+            clip,
             current_frame: 0,
+            entered_frame: Some(0),
         }
     }
 
-    /// Draw the current frame of the animation centered around the given position.
-    pub fn draw_current_centered(&self, x: f32, y: f32) {
+    /// Plays another clip, keeping the current frame when it exists in the clip.
+    ///
+    /// Past the end of the clip, a repeating clip restarts while the others stay on
+    /// their last frame, or stay finished if the previous clip was.
+    pub fn set_clip(&mut self, clip: Rc<Clip>) {
+        let was_finished = self.is_finished();
+        self.clip = clip;
+
+        if self.clip.repeating && self.is_finished() {
+            self.restart();
+        } else if was_finished {
+            self.finish();
+        } else {
+            let last_frame = self.clip.tile_indexes.len() - 1;
+            self.current_frame = self.current_frame.min(last_frame);
+            self.frame_timer.delay = self.clip.frame_durations[self.current_frame];
+        }
+    }
+
+    /// Plays the reloaded clip of the same name.
+    pub fn reload(&mut self, animations: &Animations) {
+        if let Some(clip) = animations.clip(&self.clip.name) {
+            self.set_clip(clip);
+        }
+    }
+
+    /// Draw the current frame of the animation with its pivot at the given position, the
+    /// flips of the clip being added to the ones of the options.
+    pub fn draw_current(&self, x: f32, y: f32, mut options: DrawOptions) {
         assert!(!self.is_finished(), "Cannot draw a finished animation");
-        let tile_index = self.tile_indexes[self.current_frame]; // index checked above
+        let tile_index = self.clip.tile_indexes[self.current_frame]; // index checked above
 
-        self.texture_atlas.draw_tile_centered(tile_index, x, y);
+        options.flip_x ^= self.clip.flip.0;
+        options.flip_y ^= self.clip.flip.1;
+        self.clip.texture_atlas.draw_tile(tile_index, x, y, options);
     }
 
     /// Updates the current frame.
     pub fn tick(&mut self) -> &Self {
+        self.entered_frame = None;
+
         if self.frame_timer.tick_and_finished() {
             self.current_frame += 1;
 
-            if self.repeating && self.is_finished() {
+            if self.clip.repeating && self.is_finished() {
                 self.restart();
+            } else if let Some(duration) = self.clip.frame_durations.get(self.current_frame) {
+                self.frame_timer.delay = *duration;
+                self.entered_frame = Some(self.current_frame);
             }
         }
         self
     }
 
+    /// Returns the events of the frame entered by the last tick, or of the first frame
+    /// before the first tick.
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        let entered_frame = self.entered_frame;
+
+        self.clip
+            .events
+            .iter()
+            .filter(move |(frame, _)| Some(*frame) == entered_frame)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Returns the frame of the clip triggering the event, if any.
+    pub fn event_frame(&self, event: &str) -> Option<usize> {
+        self.clip
+            .events
+            .iter()
+            .find(|(_, name)| name == event)
+            .map(|(frame, _)| *frame)
+    }
+
+    /// Returns the number of frames of the clip.
+    pub fn frame_count(&self) -> usize {
+        self.clip.tile_indexes.len()
+    }
+
+    /// Returns the part of the current frame already displayed, between 0 and 1.
+    pub fn frame_progress(&self) -> f32 {
+        self.frame_timer.progress()
+    }
+
     /// Whether or not the animation is finished.
     pub fn is_finished(&self) -> bool {
-        self.current_frame >= self.tile_indexes.len()
+        self.current_frame >= self.clip.tile_indexes.len()
+    }
+
+    /// Ends the animation immediately.
+    pub fn finish(&mut self) {
+        self.current_frame = self.clip.tile_indexes.len();
+        self.entered_frame = None;
     }
 
     /// Restart the animation to the initial frame.
     pub fn restart(&mut self) {
         self.current_frame = 0;
+        self.entered_frame = Some(0);
+        self.frame_timer.delay = self.clip.frame_durations[0];
         self.frame_timer.restart();
     }
 }
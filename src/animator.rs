@@ -0,0 +1,382 @@
+//! A controller choosing the animation of a character from its state and direction.
+
+use std::rc::Rc;
+
+use crate::{
+    animation::{Animation, Clip, DrawOptions},
+    direction::Direction,
+    resources::Animations,
+};
+
+/// What a character is doing, each state playing the clip named
+/// `<character>_<state>_<direction>` in the animation manifest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Attack,
+    Hurt,
+    Dying,
+}
+
+impl AnimationState {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Walk => "walk",
+            Self::Attack => "attack",
+            Self::Hurt => "hurt",
+            Self::Dying => "dying",
+        }
+    }
+
+    /// Returns true for the states following the movement of the character.
+    const fn is_locomotion(self) -> bool {
+        matches!(self, Self::Idle | Self::Walk)
+    }
+
+    /// Returns the priority of the state, which cannot be interrupted by a state of a
+    /// lower priority.
+    const fn priority(self) -> u8 {
+        match self {
+            Self::Idle | Self::Walk => 0,
+            Self::Attack => 1,
+            Self::Hurt => 2,
+            Self::Dying => 3,
+        }
+    }
+}
+
+/// The animation state machine of a character.
+///
+/// The idle and walk states follow the `moving` parameter and repeat. The other states
+/// are played by the game, and go back to idle or walk once their clip is finished,
+/// except for the dying state which is final.
+pub struct Animator {
+    /// Prefix of the clip names, such as `player` or the name of one of its skins.
+    character: String,
+    state: AnimationState,
+    direction: Direction,
+    /// Whether the character moves, choosing between the walk and idle states.
+    moving: bool,
+    animation: Animation,
+}
+
+impl Animator {
+    /// Creates the animator of a character, starting with its idle or walk state.
+    ///
+    /// The manifest must have one of these two clips for each direction.
+    pub fn new(
+        character: &str,
+        direction: Direction,
+        moving: bool,
+        animations: &Animations,
+    ) -> Self {
+        let state = locomotion(moving);
+        let clip = clip(character, state, direction, animations)
+            .expect("Idle or walk clips checked when loaded");
+
+        Self {
+            character: character.to_owned(),
+            state,
+            direction,
+            moving,
+            animation: Animation::new(clip),
+        }
+    }
+
+    pub const fn state(&self) -> AnimationState {
+        self.state
+    }
+
+    pub const fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    /// Returns true once the dying clip is finished, or if the character has none.
+    pub fn is_dead(&self) -> bool {
+        self.state == AnimationState::Dying && self.animation.is_finished()
+    }
+
+    /// Returns true if the state can interrupt the current one, no state of a higher
+    /// priority playing.
+    pub fn can_play(&self, state: AnimationState) -> bool {
+        let interrupted = self.state.priority() <= state.priority() || self.animation.is_finished();
+
+        self.state != AnimationState::Dying && interrupted
+    }
+
+    /// Plays an attack, hurt or dying state, unless a state of a higher priority is
+    /// playing. Playing the current state again restarts it.
+    ///
+    /// A state without a clip is skipped, except the dying state which is then
+    /// immediately finished.
+    pub fn play(&mut self, state: AnimationState, animations: &Animations) {
+        if self.can_play(state) {
+            self.enter(state, animations);
+        }
+    }
+
+    /// Faces the direction, keeping the frame of the current clip.
+    pub fn set_direction(&mut self, direction: Direction, animations: &Animations) {
+        if direction == self.direction {
+            return;
+        }
+        self.direction = direction;
+
+        if let Some(clip) = clip(&self.character, self.state, direction, animations) {
+            self.animation.set_clip(clip);
+        }
+    }
+
+    /// Walks or stays idle, the change being visible in the idle and walk states.
+    pub fn set_moving(&mut self, moving: bool, animations: &Animations) {
+        self.moving = moving;
+
+        if self.state.is_locomotion() && self.state != locomotion(moving) {
+            self.enter(locomotion(moving), animations);
+        }
+    }
+
+    /// Updates the animation, going back to idle or walk once a clip is finished.
+    pub fn update(&mut self, animations: &Animations) {
+        self.animation.tick();
+
+        if self.animation.is_finished()
+            && matches!(self.state, AnimationState::Attack | AnimationState::Hurt)
+        {
+            self.enter(locomotion(self.moving), animations);
+        }
+    }
+
+    /// Returns the events of the frame entered by the last update.
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.animation.events()
+    }
+
+    /// Plays the reloaded clip of the current state.
+    pub fn reload(&mut self, animations: &Animations) {
+        self.animation.reload(animations);
+    }
+
+    pub fn draw(&self, x: f32, y: f32, options: DrawOptions) {
+        if !self.animation.is_finished() {
+            self.animation.draw_current(x, y, options);
+        }
+    }
+
+    fn enter(&mut self, state: AnimationState, animations: &Animations) {
+        match clip(&self.character, state, self.direction, animations) {
+            Some(clip) => {
+                self.state = state;
+                self.animation = Animation::new(clip);
+            }
+            None if state == AnimationState::Dying => {
+                self.state = state;
+                self.animation.finish();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Returns the clip of the state, the idle and walk states sharing their clip when the
+/// character has only one of them.
+fn clip(
+    character: &str,
+    state: AnimationState,
+    direction: Direction,
+    animations: &Animations,
+) -> Option<Rc<Clip>> {
+    let clip = |state: AnimationState| {
+        animations.clip(&format!(
+            "{character}_{}_{}",
+            state.name(),
+            direction.name()
+        ))
+    };
+
+    clip(state).or_else(|| match state {
+        AnimationState::Idle => clip(AnimationState::Walk),
+        AnimationState::Walk => clip(AnimationState::Idle),
+        _ => None,
+    })
+}
+
+const fn locomotion(moving: bool) -> AnimationState {
+    if moving {
+        AnimationState::Walk
+    } else {
+        AnimationState::Idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use macroquad::texture::Texture2D;
+
+    use super::*;
+    use crate::{animation::TextureAtlas, timers::skip_game_time};
+
+    /// Duration of every frame of the clips.
+    const FRAME: f64 = 0.1;
+
+    /// Creates the animations of a knight, with a clip of the given number of frames for
+    /// each state, in every direction.
+    fn animations(states: &[(AnimationState, usize)]) -> Animations {
+        let atlas = Rc::new(TextureAtlas::from_tiles(Texture2D::empty(), Vec::new()));
+        let mut clips = BTreeMap::new();
+
+        for (state, frames) in states {
+            for direction in Direction::iter() {
+                let name = format!("knight_{}_{}", state.name(), direction.name());
+                let clip = Clip::new(
+                    name.clone(),
+                    atlas.clone(),
+                    vec![0; *frames],
+                    vec![FRAME; *frames],
+                    Vec::new(),
+                    state.is_locomotion(),
+                );
+                clips.insert(name, Rc::new(clip));
+            }
+        }
+        Animations::from_clips(clips)
+    }
+
+    fn knight(animations: &Animations) -> Animator {
+        Animator::new("knight", Direction::Down, false, animations)
+    }
+
+    /// Plays the given number of frames of the clips, updating the animator after each.
+    fn play_frames(animator: &mut Animator, frames: usize, animations: &Animations) {
+        for _ in 0..frames {
+            skip_game_time(FRAME * 1.01);
+            animator.update(animations);
+        }
+    }
+
+    #[test]
+    fn idle_and_walk_share_their_clip_when_one_is_missing() {
+        let animations = animations(&[(AnimationState::Walk, 4)]);
+        let mut animator = knight(&animations);
+        assert_eq!(animator.state(), AnimationState::Idle);
+
+        animator.set_moving(true, &animations);
+        assert_eq!(animator.state(), AnimationState::Walk);
+        assert_eq!(animator.animation().frame_count(), 4);
+    }
+
+    #[test]
+    fn attack_goes_back_to_locomotion_once_finished() {
+        let animations = animations(&[(AnimationState::Idle, 1), (AnimationState::Attack, 3)]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Attack, &animations);
+        play_frames(&mut animator, 2, &animations);
+        assert_eq!(animator.state(), AnimationState::Attack);
+
+        // the character started moving during the attack
+        animator.set_moving(true, &animations);
+        assert_eq!(animator.state(), AnimationState::Attack);
+        play_frames(&mut animator, 1, &animations);
+        assert_eq!(animator.state(), AnimationState::Walk);
+    }
+
+    #[test]
+    fn lower_priorities_do_not_interrupt() {
+        let animations = animations(&[
+            (AnimationState::Idle, 1),
+            (AnimationState::Attack, 3),
+            (AnimationState::Hurt, 3),
+        ]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Hurt, &animations);
+        animator.play(AnimationState::Attack, &animations);
+        assert_eq!(animator.state(), AnimationState::Hurt);
+
+        play_frames(&mut animator, 3, &animations);
+        animator.play(AnimationState::Attack, &animations);
+        animator.play(AnimationState::Hurt, &animations);
+        assert_eq!(animator.state(), AnimationState::Hurt);
+    }
+
+    #[test]
+    fn hurt_refuses_an_attack_until_finished() {
+        let animations = animations(&[
+            (AnimationState::Idle, 1),
+            (AnimationState::Attack, 3),
+            (AnimationState::Hurt, 3),
+        ]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Hurt, &animations);
+        play_frames(&mut animator, 2, &animations);
+        assert!(!animator.can_play(AnimationState::Attack));
+
+        play_frames(&mut animator, 1, &animations);
+        assert_eq!(animator.state(), AnimationState::Idle);
+        assert!(animator.can_play(AnimationState::Attack));
+    }
+
+    #[test]
+    fn playing_the_same_state_restarts_it() {
+        let animations = animations(&[(AnimationState::Idle, 1), (AnimationState::Attack, 3)]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Attack, &animations);
+        play_frames(&mut animator, 1, &animations);
+        assert_eq!(animator.animation().current_frame, 1);
+
+        animator.play(AnimationState::Attack, &animations);
+        assert_eq!(animator.animation().current_frame, 0);
+    }
+
+    #[test]
+    fn dying_is_final() {
+        let animations = animations(&[
+            (AnimationState::Walk, 2),
+            (AnimationState::Hurt, 2),
+            (AnimationState::Dying, 2),
+        ]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Dying, &animations);
+        animator.play(AnimationState::Hurt, &animations);
+        assert_eq!(animator.state(), AnimationState::Dying);
+        assert!(!animator.is_dead());
+
+        play_frames(&mut animator, 2, &animations);
+        assert!(animator.is_dead());
+        animator.set_moving(true, &animations);
+        assert_eq!(animator.state(), AnimationState::Dying);
+    }
+
+    #[test]
+    fn states_without_clip_are_skipped_except_dying() {
+        let animations = animations(&[(AnimationState::Idle, 1)]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Hurt, &animations);
+        assert_eq!(animator.state(), AnimationState::Idle);
+
+        animator.play(AnimationState::Dying, &animations);
+        assert!(animator.is_dead());
+    }
+
+    #[test]
+    fn turning_keeps_the_frame() {
+        let animations = animations(&[(AnimationState::Idle, 1), (AnimationState::Attack, 3)]);
+        let mut animator = knight(&animations);
+
+        animator.play(AnimationState::Attack, &animations);
+        play_frames(&mut animator, 1, &animations);
+        animator.set_direction(Direction::Left, &animations);
+
+        assert_eq!(animator.state(), AnimationState::Attack);
+        assert_eq!(animator.animation().current_frame, 1);
+    }
+}
@@ -0,0 +1,418 @@
+//! An importer of the sprite sheets exported by Aseprite, in the JSON array format.
+//!
+//! The frames become the tiles of an atlas, the tags become clips named after the
+//! atlas and the tag, with the events written in their user data, and the slice named
+//! `pivot` sets the pivot of the frames.
+
+use std::{collections::BTreeMap, rc::Rc};
+
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+
+use crate::{
+    animation::{Clip, TextureAtlas, Tile},
+    assets::{scalable_texture, Assets},
+};
+use slice_key::SliceKey;
+
+/// Name of the slice whose pivot is the pivot of the frames.
+const PIVOT_SLICE: &str = "pivot";
+
+#[derive(DeJson)]
+struct Sheet {
+    frames: Vec<Frame>,
+    meta: Meta,
+}
+
+#[derive(DeJson)]
+struct Frame {
+    /// Rectangle of the frame in the image.
+    #[nserde(rename = "frame")]
+    source: SheetRect,
+    #[nserde(default)]
+    rotated: bool,
+    /// Rectangle of the frame in the original sprite, smaller than it when trimmed.
+    #[nserde(rename = "spriteSourceSize")]
+    sprite_source_size: SheetRect,
+    #[nserde(rename = "sourceSize")]
+    source_size: Size,
+    /// Duration of the frame, in milliseconds.
+    duration: u32,
+}
+
+#[derive(DeJson)]
+struct Meta {
+    /// Image of the sheet, relative to the JSON file.
+    image: String,
+    #[nserde(default, rename = "frameTags")]
+    frame_tags: Vec<Tag>,
+    #[nserde(default)]
+    slices: Vec<Slice>,
+}
+
+#[derive(DeJson)]
+struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    /// Either `forward`, `reverse`, `pingpong` or `pingpong_reverse`.
+    direction: String,
+    /// Number of times the tag is played, empty or 0 when it repeats infinitely.
+    #[nserde(default)]
+    repeat: String,
+    /// User data of the tag, listing its events as `name:frame` separated by spaces,
+    /// the frames being counted from the start of the clip.
+    #[nserde(default)]
+    data: String,
+}
+
+#[derive(DeJson)]
+struct Slice {
+    name: String,
+    keys: Vec<SliceKey>,
+}
+
+/// The keys of the slices, in a module of their own to scope the lint allowed in the code
+/// derived for their optional pivot.
+#[allow(clippy::question_mark)]
+mod slice_key {
+    use nanoserde::DeJson;
+
+    use super::{Point, SheetRect};
+
+    /// The slice from a frame to the next key.
+    #[derive(DeJson)]
+    pub struct SliceKey {
+        pub frame: usize,
+        pub bounds: SheetRect,
+        #[nserde(default)]
+        pub pivot: Option<Point>,
+    }
+}
+
+#[derive(DeJson, Clone, Copy)]
+struct SheetRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(DeJson, Clone, Copy)]
+struct Size {
+    w: f32,
+    h: f32,
+}
+
+#[derive(DeJson, Clone, Copy)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+/// Loads the sheet exported in the given file as the atlas of the given name, with the
+/// clips of its tags.
+///
+/// Returns the atlas, the clips, the file of the image, relative to the asset root, and
+/// the decoded image.
+pub async fn load(
+    name: &str,
+    file: &str,
+    assets: &Assets,
+) -> Result<(Rc<TextureAtlas>, BTreeMap<String, Rc<Clip>>, String, Image), String> {
+    let path = &assets.path(file);
+    let content = assets.read_string(file).await?;
+    let sheet = Sheet::deserialize_json(&content)
+        .map_err(|err| format!("Invalid Aseprite sheet {path}: {err}"))?;
+
+    let image_file = match file.rsplit_once('/') {
+        Some((directory, _)) => format!("{directory}/{}", sheet.meta.image),
+        None => sheet.meta.image.clone(),
+    };
+    let image = assets.image(&image_file).await?;
+
+    let image_size = vec2(f32::from(image.width), f32::from(image.height));
+    let atlas = Rc::new(TextureAtlas::from_tiles(
+        scalable_texture(&image),
+        sheet.tiles(path, image_size)?,
+    ));
+    let clips = sheet.clips(name, &atlas, path)?;
+
+    Ok((atlas, clips, image_file, image))
+}
+
+impl Sheet {
+    /// Returns the tiles of the frames, checking that they fit in the image.
+    fn tiles(&self, path: &str, image_size: Vec2) -> Result<Vec<Tile>, String> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let SheetRect { x, y, w, h } = frame.source;
+
+                if frame.rotated {
+                    return Err(format!("The frame {index} of {path} is rotated"));
+                }
+                if x + w > image_size.x || y + h > image_size.y {
+                    return Err(format!("The frame {index} of {path} is out of its image"));
+                }
+
+                let trim = vec2(frame.sprite_source_size.x, frame.sprite_source_size.y);
+                Ok(Tile {
+                    source: Rect::new(x, y, w, h),
+                    pivot: self.pivot(index, frame) - trim,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the pivot of the frame in the original sprite, its center if the sheet
+    /// has no pivot for it.
+    fn pivot(&self, index: usize, frame: &Frame) -> Vec2 {
+        let center = vec2(frame.source_size.w / 2., frame.source_size.h / 2.);
+
+        self.meta
+            .slices
+            .iter()
+            .find(|slice| slice.name == PIVOT_SLICE)
+            .and_then(|slice| slice.keys.iter().rev().find(|key| key.frame <= index))
+            .and_then(|key| {
+                let pivot = key.pivot?;
+                Some(vec2(key.bounds.x + pivot.x, key.bounds.y + pivot.y))
+            })
+            .unwrap_or(center)
+    }
+
+    /// Returns the clips of the tags, named after the atlas and the tag.
+    fn clips(
+        &self,
+        name: &str,
+        atlas: &Rc<TextureAtlas>,
+        path: &str,
+    ) -> Result<BTreeMap<String, Rc<Clip>>, String> {
+        self.meta
+            .frame_tags
+            .iter()
+            .map(|tag| {
+                let clip_name = format!("{name}_{}", tag.name);
+                let clip = self.clip(tag, &clip_name, atlas, path)?;
+                Ok((clip_name, Rc::new(clip)))
+            })
+            .collect()
+    }
+
+    /// Returns the clip of the frames of the tag, played in its direction.
+    fn clip(
+        &self,
+        tag: &Tag,
+        name: &str,
+        atlas: &Rc<TextureAtlas>,
+        path: &str,
+    ) -> Result<Clip, String> {
+        if tag.from > tag.to || tag.to >= self.frames.len() {
+            return Err(format!(
+                "The tag {} of {path} is out of its frames",
+                tag.name
+            ));
+        }
+
+        let forward: Vec<_> = (tag.from..=tag.to).collect();
+        let backward: Vec<_> = forward.iter().rev().copied().collect();
+        // the ends are not repeated when going back and forth
+        let inner = |frames: &[usize]| {
+            let count = frames.len().saturating_sub(2);
+            frames
+                .iter()
+                .skip(1)
+                .take(count)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+
+        let tiles = match tag.direction.as_str() {
+            "forward" => forward,
+            "reverse" => backward,
+            "pingpong" => [forward, inner(&backward)].concat(),
+            "pingpong_reverse" => [backward, inner(&forward)].concat(),
+            direction => {
+                return Err(format!(
+                    "Unknown direction {direction} of the tag {} of {path}",
+                    tag.name
+                ))
+            }
+        };
+        if let Some(tile) = tiles.iter().find(|tile| self.frames[**tile].duration == 0) {
+            return Err(format!("The frame {tile} of {path} has no duration"));
+        }
+        let durations = tiles
+            .iter()
+            .map(|tile| f64::from(self.frames[*tile].duration) / 1000.)
+            .collect();
+        let repeating = tag.repeat.is_empty() || tag.repeat == "0";
+        let events = tag
+            .data
+            .split_whitespace()
+            .map(|event| {
+                event
+                    .split_once(':')
+                    .and_then(|(event, frame)| Some((frame.parse().ok()?, event.to_owned())))
+                    .filter(|(frame, _)| *frame < tiles.len())
+                    .ok_or_else(|| {
+                        format!("Invalid event {event} of the tag {} of {path}", tag.name)
+                    })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Clip::new(
+            name.to_owned(),
+            atlas.clone(),
+            tiles,
+            durations,
+            events,
+            repeating,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sheet of four frames in a row, the last one trimmed, with two tags and a pivot.
+    const SHEET: &str = r#"{
+        "frames": [
+            { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 },
+              "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 },
+              "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+            { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 },
+              "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 },
+              "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+            { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 },
+              "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 },
+              "sourceSize": { "w": 16, "h": 16 }, "duration": 200 },
+            { "frame": { "x": 48, "y": 0, "w": 12, "h": 14 },
+              "spriteSourceSize": { "x": 2, "y": 1, "w": 12, "h": 14 },
+              "sourceSize": { "w": 16, "h": 16 }, "duration": 100 }
+        ],
+        "meta": {
+            "image": "knight.png",
+            "frameTags": [
+                { "name": "walk", "from": 0, "to": 3, "direction": "forward",
+                  "data": "step:1 step:3" },
+                { "name": "attack", "from": 1, "to": 3, "direction": "pingpong",
+                  "repeat": "1" }
+            ],
+            "slices": [
+                { "name": "pivot", "keys": [
+                    { "frame": 0, "bounds": { "x": 4, "y": 8, "w": 8, "h": 8 },
+                      "pivot": { "x": 4, "y": 6 } }
+                ] }
+            ]
+        }
+    }"#;
+
+    fn sheet() -> Sheet {
+        Sheet::deserialize_json(SHEET).unwrap()
+    }
+
+    fn atlas(sheet: &Sheet) -> Rc<TextureAtlas> {
+        let tiles = sheet.tiles("knight.json", vec2(64., 16.)).unwrap();
+        Rc::new(TextureAtlas::from_tiles(Texture2D::empty(), tiles))
+    }
+
+    fn tag(direction: &str, data: &str) -> Tag {
+        Tag {
+            name: "tag".to_owned(),
+            from: 1,
+            to: 3,
+            direction: direction.to_owned(),
+            repeat: String::new(),
+            data: data.to_owned(),
+        }
+    }
+
+    fn clip(sheet: &Sheet, tag: &Tag) -> Result<Clip, String> {
+        sheet.clip(tag, "knight_tag", &atlas(sheet), "knight.json")
+    }
+
+    #[test]
+    fn tags_become_clips_named_after_the_atlas() {
+        let sheet = sheet();
+        let clips = sheet
+            .clips("knight", &atlas(&sheet), "knight.json")
+            .unwrap();
+
+        let names: Vec<_> = clips.keys().map(String::as_str).collect();
+        assert_eq!(names, ["knight_attack", "knight_walk"]);
+
+        let walk = &clips["knight_walk"];
+        assert_eq!(walk.tile_indexes(), [0, 1, 2, 3]);
+        assert_eq!(walk.frame_durations(), [0.1, 0.1, 0.2, 0.1]);
+        assert!(walk.is_repeating());
+        assert!(!clips["knight_attack"].is_repeating());
+    }
+
+    #[test]
+    fn directions_order_the_frames() {
+        let sheet = sheet();
+        let frames = |direction| {
+            clip(&sheet, &tag(direction, ""))
+                .unwrap()
+                .tile_indexes()
+                .to_vec()
+        };
+
+        assert_eq!(frames("forward"), [1, 2, 3]);
+        assert_eq!(frames("reverse"), [3, 2, 1]);
+        assert_eq!(frames("pingpong"), [1, 2, 3, 2]);
+        assert_eq!(frames("pingpong_reverse"), [3, 2, 1, 2]);
+        assert!(clip(&sheet, &tag("sideways", "")).is_err());
+    }
+
+    #[test]
+    fn events_are_read_from_the_user_data() {
+        let sheet = sheet();
+
+        let attack = clip(&sheet, &tag("pingpong", "swing:0 hit:3")).unwrap();
+        let events = [(0, "swing".to_owned()), (3, "hit".to_owned())];
+        assert_eq!(attack.events(), events);
+
+        // the frames are counted in the clip, which has 4 frames
+        assert!(clip(&sheet, &tag("pingpong", "hit:4")).is_err());
+        assert!(clip(&sheet, &tag("forward", "hit")).is_err());
+        assert!(clip(&sheet, &tag("forward", "hit:one")).is_err());
+    }
+
+    #[test]
+    fn frames_without_duration_are_rejected() {
+        let mut sheet = sheet();
+        sheet.frames[2].duration = 0;
+
+        assert!(clip(&sheet, &tag("forward", "")).is_err());
+    }
+
+    #[test]
+    fn pivot_slice_sets_the_pivot_of_the_trimmed_frames() {
+        let tiles = sheet().tiles("knight.json", vec2(64., 16.)).unwrap();
+
+        assert_eq!(tiles[0].pivot, vec2(8., 14.));
+        // the trimmed frame starts 2 pixels right and 1 pixel below the sprite
+        assert_eq!(tiles[3].pivot, vec2(6., 13.));
+        assert_eq!(tiles[3].source, Rect::new(48., 0., 12., 14.));
+    }
+
+    #[test]
+    fn frames_are_centered_without_pivot_slice() {
+        let mut sheet = sheet();
+        sheet.meta.slices.clear();
+        let tiles = sheet.tiles("knight.json", vec2(64., 16.)).unwrap();
+
+        assert_eq!(tiles[0].pivot, vec2(8., 8.));
+        assert_eq!(tiles[3].pivot, vec2(6., 7.));
+    }
+
+    #[test]
+    fn frames_out_of_the_image_are_rejected() {
+        assert!(sheet().tiles("knight.json", vec2(56., 16.)).is_err());
+    }
+}
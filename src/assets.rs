@@ -0,0 +1,176 @@
+//! The asset manifest, listing the files loaded by the game, and the loading of these
+//! files from the asset root.
+//!
+//! The files are decoded here rather than by macroquad, so that a missing or corrupt
+//! file is reported instead of panicking.
+//!
+//! With the `embedded-assets` feature, the files of the `resources` directory are
+//! included in the binary and never read from the disk.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use macroquad::prelude::*;
+use nanoserde::DeRon;
+
+/// Name of the directory containing the assets.
+const ROOT_DIRECTORY: &str = "resources";
+/// File of the asset manifest, in the asset root.
+const MANIFEST_FILE: &str = "assets.ron";
+
+/// The files of the `resources` directory, listed by the build script.
+#[cfg(feature = "embedded-assets")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+/// The files of the game, relative to the asset root.
+#[derive(DeRon)]
+pub struct AssetManifest {
+    pub heart: String,
+    pub empty_heart: String,
+    pub background: String,
+    pub font: String,
+    /// Sound effects, by effect name.
+    pub effects: BTreeMap<String, String>,
+    pub menu_music: String,
+    pub game_music: String,
+    /// The animation manifest.
+    pub animations: String,
+    pub tuning: String,
+}
+
+/// The asset manifest, along with the directory its files are loaded from.
+pub struct Assets {
+    root: String,
+    pub manifest: AssetManifest,
+}
+
+impl Assets {
+    /// Finds the asset root and reads its manifest.
+    pub async fn load() -> Result<Self, String> {
+        let root = find_root();
+        let path = format!("{root}/{MANIFEST_FILE}");
+
+        let content = to_string(read_file(&root, MANIFEST_FILE).await?, &path)?;
+        let manifest = AssetManifest::deserialize_ron(&content)
+            .map_err(|err| format!("Invalid asset manifest {path}: {err}"))?;
+
+        Ok(Self { root, manifest })
+    }
+
+    /// Returns the path of a file relative to the asset root.
+    pub fn path(&self, file: &str) -> String {
+        format!("{}/{file}", self.root)
+    }
+
+    /// Returns the images packed into the atlas of the textures.
+    pub fn texture_files(&self) -> [&str; 3] {
+        let manifest = &self.manifest;
+        [&manifest.heart, &manifest.empty_heart, &manifest.background]
+    }
+
+    pub async fn read(&self, file: &str) -> Result<Vec<u8>, String> {
+        read_file(&self.root, file).await
+    }
+
+    pub async fn read_string(&self, file: &str) -> Result<String, String> {
+        to_string(self.read(file).await?, &self.path(file))
+    }
+
+    /// Reads a text file without waiting, which the browser only supports for the
+    /// embedded files.
+    pub fn read_string_now(&self, file: &str) -> Result<String, String> {
+        let path = self.path(file);
+
+        #[cfg(feature = "embedded-assets")]
+        let bytes = find_embedded(&path, file).map(<[u8]>::to_vec);
+        #[cfg(all(not(feature = "embedded-assets"), not(target_arch = "wasm32")))]
+        let bytes = std::fs::read(&path).map_err(|err| format!("Cannot read {path}: {err}"));
+        #[cfg(all(not(feature = "embedded-assets"), target_arch = "wasm32"))]
+        let bytes = Err(format!("Cannot read {path} without waiting in the browser"));
+
+        to_string(bytes?, &path)
+    }
+
+    /// Loads and decodes an image.
+    pub async fn image(&self, file: &str) -> Result<Image, String> {
+        let bytes = self.read(file).await?;
+        let invalid = |err| format!("Invalid image {}: {err}", self.path(file));
+
+        let image = image::load_from_memory(&bytes)
+            .map_err(|err| invalid(err.to_string()))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+
+        Ok(Image {
+            width: u16::try_from(width).map_err(|_| invalid("too wide".to_owned()))?,
+            height: u16::try_from(height).map_err(|_| invalid("too tall".to_owned()))?,
+            bytes: image.into_raw(),
+        })
+    }
+
+    /// Loads a TrueType font.
+    pub async fn font(&self, file: &str) -> Result<Font, String> {
+        let bytes = self.read(file).await?;
+
+        // macroquad panics on invalid fonts, so they are parsed once beforehand
+        fontdue::Font::from_bytes(&bytes[..], fontdue::FontSettings::default())
+            .map_err(|err| format!("Invalid font {}: {err}", self.path(file)))?;
+        Ok(load_ttf_font_from_bytes(&bytes))
+    }
+}
+
+/// Creates a texture from the image, that will be scaled to the screen.
+pub fn scalable_texture(image: &Image) -> Texture2D {
+    let texture = load_texture_from_image(image);
+    set_texture_filter(texture, FilterMode::Nearest); // scale pixels without smoothing
+    texture
+}
+
+/// Reads a file of the asset root, from the binary when the files are embedded.
+#[cfg_attr(feature = "embedded-assets", allow(clippy::unused_async))]
+async fn read_file(root: &str, file: &str) -> Result<Vec<u8>, String> {
+    let path = format!("{root}/{file}");
+
+    #[cfg(feature = "embedded-assets")]
+    return find_embedded(&path, file).map(<[u8]>::to_vec);
+
+    #[cfg(not(feature = "embedded-assets"))]
+    load_file(&path)
+        .await
+        .map_err(|err| format!("Cannot read {path}: {err:?}"))
+}
+
+#[cfg(feature = "embedded-assets")]
+fn find_embedded(path: &str, file: &str) -> Result<&'static [u8], String> {
+    embedded::FILES
+        .iter()
+        .find(|(name, _)| *name == file)
+        .map(|(_, bytes)| *bytes)
+        .ok_or_else(|| format!("Missing {path} in the embedded files"))
+}
+
+fn to_string(bytes: Vec<u8>, path: &str) -> Result<String, String> {
+    String::from_utf8(bytes).map_err(|_| format!("Invalid text in {path}"))
+}
+
+/// Returns the asset root, the first `resources` directory with a manifest next to the
+/// executable or in one of its parents, so that the game starts from any working
+/// directory.
+///
+/// Falls back to the `resources` directory of the working directory, which is also the
+/// one of the page in the browser, and the name of the embedded root.
+fn find_root() -> String {
+    #[cfg(not(any(target_arch = "wasm32", feature = "embedded-assets")))]
+    if let Some(root) = std::env::current_exe().ok().and_then(|executable| {
+        executable
+            .ancestors()
+            .skip(1)
+            .map(|directory| directory.join(ROOT_DIRECTORY))
+            .find(|root| root.join(MANIFEST_FILE).is_file())
+    }) {
+        return root.to_string_lossy().into_owned();
+    }
+
+    ROOT_DIRECTORY.to_owned()
+}
@@ -0,0 +1,72 @@
+//! Effects moving the camera and freezing the game to emphasize the hits.
+
+use macroquad::{prelude::*, rand::gen_range};
+
+use crate::GAME_WIDTH;
+
+/// Offset of the camera, in game pixels, at full trauma.
+const MAX_SHAKE_OFFSET: f32 = 6.;
+/// Trauma recovered each second.
+const TRAUMA_DECAY: f32 = 1.5;
+/// Zoom of the punches lost each second.
+const ZOOM_DECAY: f32 = 0.5;
+
+/// Screen shake, zoom punches and hit-stops, fading out over time.
+#[derive(Default)]
+pub struct CameraEffects {
+    /// Amount of shaking, between 0 and 1.
+    trauma: f32,
+    /// Zoom added to the camera by the punches.
+    zoom: f32,
+    /// Remaining duration of the hit-stop, in seconds.
+    hit_stop: f32,
+}
+
+impl CameraEffects {
+    /// Shakes the screen, the trauma adding up to a maximum of 1.
+    pub fn shake(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+
+    /// Zooms in suddenly, the camera then going back to its normal zoom.
+    pub const fn punch_zoom(&mut self, zoom: f32) {
+        self.zoom = self.zoom.max(zoom);
+    }
+
+    /// Freezes the game for the given duration.
+    pub const fn hit_stop(&mut self, seconds: f32) {
+        self.hit_stop = self.hit_stop.max(seconds);
+    }
+
+    /// Returns true during a hit-stop, when the game time must not advance.
+    pub fn is_frozen(&self) -> bool {
+        self.hit_stop > 0.
+    }
+
+    /// Fades the effects out by the duration of the frame on the game clock, to be called
+    /// once per frame.
+    ///
+    /// The duration is scaled like the game time, but taken before a hit-stop stops the
+    /// game time, which would otherwise never resume.
+    pub fn update(&mut self, delta: f32) {
+        self.hit_stop = (self.hit_stop - delta).max(0.);
+        self.trauma = TRAUMA_DECAY.mul_add(-delta, self.trauma).max(0.);
+        self.zoom = ZOOM_DECAY.mul_add(-delta, self.zoom).max(0.);
+    }
+
+    /// Returns the offset and the zoom of the camera, which are neutral when the shake
+    /// and the zoom are disabled.
+    pub fn view(&self, enabled: bool) -> (Vec2, f32) {
+        if !enabled {
+            return (Vec2::zero(), 1.);
+        }
+
+        // squared for a shake that feels stronger on big hits and fades out smoothly
+        let shake = self.trauma * self.trauma * MAX_SHAKE_OFFSET;
+        let offset = vec2(gen_range(-shake, shake), gen_range(-shake, shake));
+        // zoomed in enough to keep the edges of the game out of the screen while shaking
+        let zoom = 1. + self.zoom + 2. * shake / GAME_WIDTH;
+
+        (offset, zoom)
+    }
+}
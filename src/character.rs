@@ -1,6 +1,6 @@
-use macroquad::prelude::*;
+use macroquad::{prelude::*, rand::ChooseRandom};
 
-use crate::direction::Direction;
+use crate::{direction::Direction, timers::game_frame_time, GAME_HEIGHT, GAME_WIDTH};
 
 pub struct Character {
     pub body: Rect,
@@ -17,6 +17,26 @@ impl Character {
         }
     }
 
+    /// Creates a character placed in one side of the screen, heading to the center.
+    pub fn new_on_random_side(w: f32, h: f32) -> Self {
+        let directions: Vec<_> = Direction::iter().collect();
+
+        Self::new_heading(*directions.choose().unwrap(), w, h)
+    }
+
+    /// Creates a character placed in the side of the screen from which it heads
+    /// to the center following the given direction.
+    pub fn new_heading(direction: Direction, w: f32, h: f32) -> Self {
+        let (x, y) = match direction {
+            Direction::Down => (GAME_WIDTH / 2., 0.),           // Top
+            Direction::Up => (GAME_WIDTH / 2., GAME_HEIGHT),    // Bottom
+            Direction::Left => (GAME_WIDTH, GAME_HEIGHT / 2.0), // Right
+            Direction::Right => (0., GAME_HEIGHT / 2.),         // Left
+        };
+
+        Self::new(x, y, w, h, direction)
+    }
+
     /// Draws the hit-box of the character.
     pub fn draw_hit_box(&self) {
         let Rect { x, y, w, h } = self.body;
@@ -32,7 +52,7 @@ impl Character {
 
     /// Moves the body following the direction.
     pub fn move_body(&mut self, speed: f32) {
-        let translation = Vec2::from(self.direction) * get_frame_time() * speed;
+        let translation = Vec2::from(self.direction) * game_frame_time() * speed;
         self.body = self.body.offset(translation);
     }
 
@@ -0,0 +1,121 @@
+//! The built-in commands of the developer console.
+
+use std::str::FromStr;
+
+use crate::{
+    console::{Console, Context},
+    direction::Direction,
+    enemy::Enemy,
+    ENEMY_SIZE,
+};
+
+/// Largest number of enemies spawned by a wave.
+const MAX_WAVE: usize = 100;
+
+/// Adds the built-in commands to the console.
+///
+/// The commands changing the run mark it as cheated, so that its score is not kept as
+/// the best score.
+pub fn register(console: &mut Console) {
+    console.register("spawn", "<kind> <up|down|left|right>", spawn);
+    console.register("wave", "<count>", wave);
+    console.register("god", "", god);
+    console.register("lives", "<count>", lives);
+    console.register("score", "<score>", score);
+    console.register("timescale", "<scale>", time_scale);
+    console.register("reload", "", reload);
+}
+
+/// Spawns an enemy of the given kind, the enemy or one of its skins, coming from the
+/// given side of the screen.
+fn spawn(context: &mut Context, arguments: &[&str]) -> Result<String, String> {
+    let kind = argument::<String>(arguments, 0, "kind")?;
+    let kinds = context.animations.unlocked_skins("enemy", u32::MAX);
+    if !kinds.contains(&kind.as_str()) {
+        return Err(format!("Unknown kind {kind}, expected {}", kinds.join("|")));
+    }
+    let side = argument::<String>(arguments, 1, "side")?;
+    let side = Direction::from_name(&side).ok_or_else(|| format!("Unknown side {side}"))?;
+
+    let enemy = Enemy::new_heading(
+        &kind,
+        side.opposite(),
+        ENEMY_SIZE,
+        ENEMY_SIZE,
+        context.animations,
+    );
+    context.run.enemies.push(enemy);
+    context.run.cheated = true;
+    Ok(String::new())
+}
+
+/// Spawns enemies on random sides of the screen all at once, up to `MAX_WAVE`.
+fn wave(context: &mut Context, arguments: &[&str]) -> Result<String, String> {
+    let count = argument::<usize>(arguments, 0, "count")?.min(MAX_WAVE);
+
+    for _ in 0..count {
+        let enemy = Enemy::new_random(ENEMY_SIZE, ENEMY_SIZE, context.animations);
+        context.run.enemies.push(enemy);
+    }
+    context.run.cheated = true;
+    Ok(format!("{count} enemies spawned"))
+}
+
+#[allow(clippy::unnecessary_wraps)] // signature of the handlers
+fn god(context: &mut Context, _: &[&str]) -> Result<String, String> {
+    let run = &mut *context.run;
+    run.god_mode = !run.god_mode;
+    run.cheated = true;
+
+    Ok(format!(
+        "God mode {}",
+        if run.god_mode { "on" } else { "off" }
+    ))
+}
+
+fn lives(context: &mut Context, arguments: &[&str]) -> Result<String, String> {
+    let lives = argument(arguments, 0, "count")?;
+    context.run.life_bar.set_lives(lives);
+    context.run.cheated = true;
+
+    Ok(String::new())
+}
+
+fn score(context: &mut Context, arguments: &[&str]) -> Result<String, String> {
+    context.run.score = argument(arguments, 0, "score")?;
+    context.run.cheated = true;
+
+    Ok(String::new())
+}
+
+fn time_scale(context: &mut Context, arguments: &[&str]) -> Result<String, String> {
+    let scale: f32 = argument(arguments, 0, "scale")?;
+
+    if !(scale > 0. && scale <= 10.) {
+        return Err("The scale must be above 0 and up to 10".to_owned());
+    }
+    context.run.time_scale = scale;
+    context.run.cheated = true;
+
+    Ok(String::new())
+}
+
+/// Reads the settings and the resources again, as the watcher of the files does, to
+/// apply the changes made to them.
+#[allow(clippy::unnecessary_wraps)] // signature of the handlers
+fn reload(context: &mut Context, _: &[&str]) -> Result<String, String> {
+    context.reload = true;
+
+    Ok("Settings and resources reloaded".to_owned())
+}
+
+/// Parses the argument at the given index.
+fn argument<T: FromStr>(arguments: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let argument = arguments
+        .get(index)
+        .ok_or_else(|| format!("Missing {name}"))?;
+
+    argument
+        .parse()
+        .map_err(|_| format!("Invalid {name} {argument}"))
+}
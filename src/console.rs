@@ -0,0 +1,175 @@
+//! A drop-down developer console running commands on the game in progress.
+//!
+//! Its commands being cheats, it is only built in the debug builds.
+
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+
+use crate::{
+    resources::{Animations, Fonts},
+    run::Run,
+    GAME_WIDTH, MARGIN,
+};
+
+/// Key opening and closing the console.
+const TOGGLE_KEY: KeyCode = KeyCode::GraveAccent;
+/// Number of output lines kept and displayed.
+const OUTPUT_LINES: usize = 8;
+/// Spacing between the lines of the console.
+const LINE_HEIGHT: f32 = 9.;
+
+/// What the commands can act on.
+pub struct Context<'a> {
+    pub run: &'a mut Run,
+    pub animations: &'a Animations,
+    /// Set by a command to reload the settings and the resources, once it has run.
+    pub reload: bool,
+}
+
+/// Runs a command with its arguments, returning the message to display.
+pub type Handler = fn(&mut Context, &[&str]) -> Result<String, String>;
+
+struct Command {
+    /// Arguments of the command, shown by `help`.
+    usage: &'static str,
+    handler: Handler,
+}
+
+#[derive(Default)]
+pub struct Console {
+    open: bool,
+    /// Command being typed.
+    line: String,
+    /// Commands entered, the most recent last.
+    history: Vec<String>,
+    /// Position in the history while browsing it with the up and down keys.
+    history_index: Option<usize>,
+    /// Last lines displayed, the most recent last.
+    output: Vec<String>,
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl Console {
+    /// Adds a command, replacing any command with the same name.
+    pub fn register(&mut self, name: &'static str, usage: &'static str, handler: Handler) {
+        self.commands.insert(name, Command { usage, handler });
+    }
+
+    pub const fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handles the typing, to be called once per frame.
+    ///
+    /// Returns the command entered during the frame, if any.
+    pub fn update(&mut self) -> Option<String> {
+        // Always drained, so that the characters typed while closed are not kept
+        let typed = typed_chars();
+
+        if is_key_pressed(TOGGLE_KEY) {
+            self.open = !self.open;
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        } else if is_key_pressed(KeyCode::Up) {
+            self.browse_history(true);
+        } else if is_key_pressed(KeyCode::Down) {
+            self.browse_history(false);
+        } else if is_key_pressed(KeyCode::Enter) {
+            self.history_index = None;
+            return Some(std::mem::take(&mut self.line));
+        }
+
+        let printable = typed
+            .into_iter()
+            .filter(|char| !char.is_control() && *char != '`');
+        self.line.extend(printable);
+        None
+    }
+
+    /// Moves to an older or a more recent command of the history.
+    fn browse_history(&mut self, older: bool) {
+        let index = match (self.history_index, older) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => Some(index + 1).filter(|index| *index < self.history.len()),
+        };
+
+        self.history_index = index;
+        self.line = index.map_or_else(String::new, |index| self.history[index].clone());
+    }
+
+    /// Runs the command line, displaying its result.
+    pub fn execute(&mut self, line: &str, context: &mut Context) {
+        let words: Vec<_> = line.split_whitespace().collect();
+        let Some((name, arguments)) = words.split_first() else {
+            return;
+        };
+
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_owned());
+        }
+        self.print(format!("> {line}"));
+
+        let result = match self.commands.get(name) {
+            Some(command) => (command.handler)(context, arguments),
+            None if *name == "help" => Ok(self.help()),
+            None => Err(format!("Unknown command {name}, try help")),
+        };
+        match result {
+            Ok(message) => message.lines().for_each(|line| self.print(line.to_owned())),
+            Err(message) => self.print(format!("Error: {message}")),
+        }
+    }
+
+    fn help(&self) -> String {
+        let commands: Vec<_> = self
+            .commands
+            .iter()
+            .map(|(name, command)| format!("{name} {}", command.usage).trim_end().to_owned())
+            .collect();
+        commands.join("\n")
+    }
+
+    fn print(&mut self, message: String) {
+        self.output.push(message);
+
+        if self.output.len() > OUTPUT_LINES {
+            self.output.remove(0);
+        }
+    }
+
+    /// Draws the console over the top of the game, if it is open.
+    pub fn draw(&self, fonts: &Fonts) {
+        if !self.open {
+            return;
+        }
+
+        let height = ((OUTPUT_LINES + 1) as f32).mul_add(LINE_HEIGHT, 2. * MARGIN);
+        draw_rectangle(0., 0., GAME_WIDTH, height, Color::new(0., 0., 0., 0.8));
+
+        let prompt = format!("> {}_", self.line);
+        let lines = self.output.iter().chain(std::iter::once(&prompt));
+        let first = OUTPUT_LINES - self.output.len();
+
+        for (index, line) in lines.enumerate() {
+            let y = ((first + index) as f32).mul_add(LINE_HEIGHT, MARGIN);
+            Fonts::draw_right(line, MARGIN, y, fonts.sized(8));
+        }
+    }
+}
+
+/// Returns the characters typed since the last call, in order.
+fn typed_chars() -> Vec<char> {
+    // The queue of macroquad is consumed from its end
+    let mut chars: Vec<_> = std::iter::from_fn(get_char_pressed).collect();
+    chars.reverse();
+    chars
+}
@@ -0,0 +1,125 @@
+//! An overlay showing the internal state of the game, toggled at runtime.
+
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::{character::Character, resources::Fonts, run::Run, GAME_HEIGHT, MARGIN};
+
+/// Key showing or hiding the overlay.
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+/// Key selecting the next entity to inspect.
+const SELECT_KEY: KeyCode = KeyCode::Tab;
+/// Number of frames shown in the frame time graph.
+const GRAPH_FRAMES: usize = 100;
+/// Height of the graph, in game pixels, for each millisecond of a frame.
+const GRAPH_SCALE: f32 = 0.5;
+/// Frame time at 60 frames per second, marked in the graph.
+const TARGET_FRAME_TIME: f32 = 1. / 60.;
+
+/// Draws the hit boxes, the timers, the frame times and the state of an entity.
+#[derive(Default)]
+pub struct DebugOverlay {
+    visible: bool,
+    /// Durations of the last frames, the most recent last.
+    frame_times: VecDeque<f32>,
+    /// Index of the inspected entity, the player coming before the enemies.
+    selected: usize,
+}
+
+impl DebugOverlay {
+    /// Handles the keys of the overlay, unless they are typed in the open console, and
+    /// records the frame time, to be called once per frame.
+    pub fn update(&mut self, console_open: bool) {
+        if console_open {
+            // the keys are typed in the console
+        } else if is_key_pressed(TOGGLE_KEY) {
+            self.visible = !self.visible;
+        } else if self.visible && is_key_pressed(SELECT_KEY) {
+            self.selected += 1;
+        }
+
+        if self.frame_times.len() == GRAPH_FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(get_frame_time());
+    }
+
+    pub const fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn draw(&self, run: &Run, fonts: &Fonts) {
+        if !self.visible {
+            return;
+        }
+
+        let Rect { x, y, w, h } = run.player.slash_attack();
+        draw_rectangle_lines(x, y, w, h, 1., SKYBLUE);
+
+        let (name, character, state) = self.selected_entity(run);
+        let Rect { x, y, w, h } = character.body;
+        draw_rectangle_lines(x - 1., y - 1., w + 2., h + 2., 1., YELLOW);
+
+        let (x, y) = character.position();
+        let lines = [
+            format!("FPS: {}", get_fps()),
+            format!("Spawn in: {:.2}s", run.enemy_spawner.remaining()),
+            format!("Cooldown: {:.2}s", run.attack_cooldown.remaining()),
+            format!("Enemies: {}", run.enemies.len()),
+            format!("Particles: {}", run.particles.count()),
+            format!("[Tab] {name}: ({x:.0}, {y:.0})"),
+            format!("  {:?}, {state}", character.direction),
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            let y = (index as f32).mul_add(9., 20.);
+            Fonts::draw_right(line, MARGIN, y, fonts.sized(8));
+        }
+
+        self.draw_frame_times();
+    }
+
+    /// Returns the name, the character and the state of the inspected entity.
+    fn selected_entity<'a>(&self, run: &'a Run) -> (&'static str, &'a Character, String) {
+        let count = 1 + run.enemies.len();
+
+        match self.selected % count {
+            0 => {
+                let (state, frame) = run.player.animation_frame();
+                let state = format!("{state:?} frame {frame}");
+                ("Player", &run.player.character, state)
+            }
+            index => {
+                let enemy = &run.enemies[index - 1];
+                let state = format!(
+                    "{:?} frame {}",
+                    enemy.animator.state(),
+                    enemy.animator.animation().current_frame
+                );
+                ("Enemy", &enemy.character, state)
+            }
+        }
+    }
+
+    /// Draws a bar for each recent frame, with a line at the target frame time.
+    fn draw_frame_times(&self) {
+        let bottom = GAME_HEIGHT - MARGIN;
+        let target = TARGET_FRAME_TIME * 1000. * GRAPH_SCALE;
+
+        for (index, frame_time) in self.frame_times.iter().enumerate() {
+            let height = frame_time * 1000. * GRAPH_SCALE;
+            let color = if height > target { RED } else { GREEN };
+            draw_rectangle(MARGIN + index as f32, bottom - height, 1., height, color);
+        }
+
+        let width = GRAPH_FRAMES as f32;
+        draw_line(
+            MARGIN,
+            bottom - target,
+            MARGIN + width,
+            bottom - target,
+            1.,
+            WHITE,
+        );
+    }
+}
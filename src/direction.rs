@@ -1,6 +1,6 @@
 use macroquad::prelude::*;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Direction {
     Up,
     Right,
@@ -15,6 +15,36 @@ impl Direction {
     pub fn iter() -> impl Iterator<Item = Self> {
         [Up, Right, Down, Left].iter().copied()
     }
+
+    pub const fn opposite(self) -> Self {
+        match self {
+            Up => Down,
+            Right => Left,
+            Down => Up,
+            Left => Right,
+        }
+    }
+
+    /// Returns the lowercase name of the direction.
+    pub fn name(self) -> String {
+        format!("{self:?}").to_lowercase()
+    }
+
+    /// Returns the direction with the given lowercase name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|direction| direction.name() == name)
+    }
+
+    /// Returns the direction closest to the given vector, if it is not null.
+    pub fn from_vector(vector: Vec2) -> Option<Self> {
+        if vector == Vec2::zero() {
+            None
+        } else if vector.x.abs() > vector.y.abs() {
+            Some(if vector.x > 0. { Right } else { Left })
+        } else {
+            Some(if vector.y > 0. { Down } else { Up })
+        }
+    }
 }
 
 impl From<Direction> for Vec2 {
@@ -29,14 +59,3 @@ impl From<Direction> for Vec2 {
         Self { x, y }
     }
 }
-
-impl From<Direction> for KeyCode {
-    fn from(direction: Direction) -> Self {
-        match direction {
-            Up => Self::Up,
-            Right => Self::Right,
-            Down => Self::Down,
-            Left => Self::Left,
-        }
-    }
-}
@@ -1,44 +1,76 @@
-use macroquad::{prelude::*, rand::ChooseRandom};
+use macroquad::rand::ChooseRandom;
 
 use crate::{
-    animation::Animation, character::Character, direction::Direction, resources::Animations,
-    ENEMY_SPEED, GAME_HEIGHT, GAME_WIDTH,
+    animation::DrawOptions,
+    animator::{AnimationState, Animator},
+    character::Character,
+    direction::Direction,
+    resources::Animations,
 };
 
 pub struct Enemy {
     pub character: Character,
-    pub animation: Animation,
+    pub animator: Animator,
+    /// Whether the enemy can still kill or be killed, its dying animation playing once
+    /// it is not.
     pub alive: bool,
 }
 
 impl Enemy {
-    /// Creates a random enemy placed in one side of the screen.
+    /// Creates a random enemy placed in one side of the screen, with a random skin or
+    /// without any, the enemies using all their skins.
     pub fn new_random(w: f32, h: f32, animations: &Animations) -> Self {
-        let directions: Vec<_> = Direction::iter().collect();
-        let direction = *directions.choose().unwrap();
+        let skins = animations.unlocked_skins("enemy", u32::MAX);
+        let skin = skins.choose().expect("The enemy itself is listed");
 
-        let (x, y) = match direction {
-            Direction::Down => (GAME_WIDTH / 2., 0.),           // Top
-            Direction::Up => (GAME_WIDTH / 2., GAME_HEIGHT),    // Bottom
-            Direction::Left => (GAME_WIDTH, GAME_HEIGHT / 2.0), // Right
-            Direction::Right => (0., GAME_HEIGHT / 2.),         // Left
-        };
+        Self::from_character(Character::new_on_random_side(w, h), skin, animations)
+    }
+
+    /// Creates an enemy of the given kind, the enemy or one of its skins, heading to the
+    /// center following the given direction.
+    #[cfg_attr(not(debug_assertions), allow(dead_code))] // spawned from the console
+    pub fn new_heading(
+        kind: &str,
+        direction: Direction,
+        w: f32,
+        h: f32,
+        animations: &Animations,
+    ) -> Self {
+        Self::from_character(Character::new_heading(direction, w, h), kind, animations)
+    }
 
+    fn from_character(character: Character, skin: &str, animations: &Animations) -> Self {
         Self {
-            character: Character::new(x, y, w, h, direction),
-            animation: animations.enemy_walking(direction),
+            animator: Animator::new(skin, character.direction, true, animations),
+            character,
             alive: true,
         }
     }
 
-    /// Moves the enemy following its direction and update the animation.
-    pub fn move_and_animate(&mut self) {
-        self.character.move_body(ENEMY_SPEED);
-        self.animation.tick();
+    /// Moves the enemy following its direction, while it is alive, and update the
+    /// animation.
+    pub fn move_and_animate(&mut self, speed: f32, animations: &Animations) {
+        if self.alive {
+            self.character.move_body(speed);
+        }
+        self.animator
+            .set_moving(self.alive && speed > 0., animations);
+        self.animator.update(animations);
+    }
+
+    /// Stops the enemy and plays its dying animation.
+    pub fn die(&mut self, animations: &Animations) {
+        self.alive = false;
+        self.animator.play(AnimationState::Dying, animations);
+    }
+
+    /// Returns true once the enemy is dead and its dying animation is over.
+    pub fn is_gone(&self) -> bool {
+        !self.alive && self.animator.is_dead()
     }
 
     pub fn draw(&self) {
         let (x, y) = self.character.position();
-        self.animation.draw_current_centered(x, y)
+        self.animator.draw(x, y, DrawOptions::default());
     }
 }
@@ -0,0 +1,386 @@
+//! Gamepad input, read from a backend of the platform and mapped to the input actions.
+
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+
+use crate::input::Action;
+
+/// Tilt of the analog stick under which it is considered at rest.
+const STICK_DEAD_ZONE: f32 = 0.35;
+
+/// A button of a gamepad, named after the standard layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    South,
+    East,
+    West,
+    North,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Button {
+    /// Returns the buttons triggering the action.
+    const fn for_action(action: Action) -> &'static [Self] {
+        match action {
+            Action::Up => &[Self::DPadUp],
+            Action::Down => &[Self::DPadDown],
+            Action::Left => &[Self::DPadLeft],
+            Action::Right => &[Self::DPadRight],
+            Action::Attack => &[Self::South, Self::West],
+            Action::Pause => &[Self::Start, Self::East],
+            Action::Confirm => &[Self::South, Self::Start],
+            Action::Mute | Action::Fullscreen => &[],
+        }
+    }
+}
+
+/// The state of a gamepad during a frame.
+#[derive(Clone, Default, Debug)]
+pub struct GamepadState {
+    /// The buttons being pressed.
+    pub buttons: Vec<Button>,
+    /// The position of the left analog stick, with `y` pointing down.
+    pub stick: Vec2,
+}
+
+impl GamepadState {
+    /// Returns the pressed buttons, the tilted stick pressing the d-pad button of its direction.
+    fn pressed_buttons(&self) -> Vec<Button> {
+        let mut buttons = self.buttons.clone();
+
+        if let Some(button) = stick_button(self.stick) {
+            if !buttons.contains(&button) {
+                buttons.push(button);
+            }
+        }
+        buttons
+    }
+}
+
+/// Returns the d-pad button of the main axis of the stick, if it is tilted out of the dead zone.
+fn stick_button(stick: Vec2) -> Option<Button> {
+    if stick.length() < STICK_DEAD_ZONE {
+        return None;
+    }
+
+    let button = if stick.x.abs() > stick.y.abs() {
+        if stick.x > 0. {
+            Button::DPadRight
+        } else {
+            Button::DPadLeft
+        }
+    } else if stick.y > 0. {
+        Button::DPadDown
+    } else {
+        Button::DPadUp
+    };
+    Some(button)
+}
+
+/// A source of gamepad states.
+pub trait GamepadBackend {
+    /// Returns the state of each connected gamepad along with its unique id.
+    fn poll(&mut self) -> Vec<(usize, GamepadState)>;
+}
+
+/// The connected gamepads, with their buttons pressed in the current and previous frames.
+pub struct Gamepads {
+    backend: Box<dyn GamepadBackend>,
+    current: BTreeMap<usize, Vec<Button>>,
+    previous: BTreeMap<usize, Vec<Button>>,
+}
+
+impl Gamepads {
+    /// Reads the gamepads using the backend of the platform.
+    pub fn new() -> Self {
+        Self::with_backend(Box::new(platform::PlatformGamepads::new()))
+    }
+
+    pub fn with_backend(backend: Box<dyn GamepadBackend>) -> Self {
+        Self {
+            backend,
+            current: BTreeMap::new(),
+            previous: BTreeMap::new(),
+        }
+    }
+
+    /// Polls the state of the gamepads, to be called once per frame.
+    pub fn update(&mut self) {
+        let current = self
+            .backend
+            .poll()
+            .iter()
+            .map(|(id, state)| (*id, state.pressed_buttons()))
+            .collect();
+
+        self.previous = std::mem::replace(&mut self.current, current);
+    }
+
+    /// Returns the number of connected gamepads.
+    pub fn connected(&self) -> usize {
+        self.current.len()
+    }
+
+    /// Returns true if a gamepad has been unplugged since the last frame.
+    pub fn just_disconnected(&self) -> bool {
+        self.previous
+            .keys()
+            .any(|id| !self.current.contains_key(id))
+    }
+
+    /// Returns true if any gamepad is pressing a button of the action.
+    pub fn is_down(&self, action: Action) -> bool {
+        let buttons = Button::for_action(action);
+
+        self.current
+            .values()
+            .any(|pressed| buttons.iter().any(|button| pressed.contains(button)))
+    }
+
+    /// Returns true if any gamepad has just pressed a button of the action.
+    pub fn is_pressed(&self, action: Action) -> bool {
+        let buttons = Button::for_action(action);
+
+        self.current.iter().any(|(id, pressed)| {
+            let previous = self.previous.get(id);
+
+            buttons.iter().any(|button| {
+                pressed.contains(button) && !previous.is_some_and(|p| p.contains(button))
+            })
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use gilrs::{Axis, Gamepad, Gilrs};
+    use macroquad::prelude::*;
+
+    use super::{Button, GamepadBackend, GamepadState};
+
+    /// Reads the gamepads with `gilrs`, or none if it is not available on the system.
+    pub struct PlatformGamepads {
+        gilrs: Option<Gilrs>,
+    }
+
+    impl PlatformGamepads {
+        pub fn new() -> Self {
+            Self {
+                gilrs: Gilrs::new().ok(),
+            }
+        }
+    }
+
+    impl GamepadBackend for PlatformGamepads {
+        fn poll(&mut self) -> Vec<(usize, GamepadState)> {
+            let Some(gilrs) = &mut self.gilrs else {
+                return Vec::new();
+            };
+            while gilrs.next_event().is_some() {} // events update the gamepads states
+
+            gilrs
+                .gamepads()
+                .map(|(id, gamepad)| (id.into(), state(&gamepad)))
+                .collect()
+        }
+    }
+
+    fn state(gamepad: &Gamepad) -> GamepadState {
+        let buttons = [
+            (gilrs::Button::South, Button::South),
+            (gilrs::Button::East, Button::East),
+            (gilrs::Button::West, Button::West),
+            (gilrs::Button::North, Button::North),
+            (gilrs::Button::Start, Button::Start),
+            (gilrs::Button::DPadUp, Button::DPadUp),
+            (gilrs::Button::DPadDown, Button::DPadDown),
+            (gilrs::Button::DPadLeft, Button::DPadLeft),
+            (gilrs::Button::DPadRight, Button::DPadRight),
+        ];
+
+        GamepadState {
+            buttons: buttons
+                .iter()
+                .filter(|(gilrs_button, _)| gamepad.is_pressed(*gilrs_button))
+                .map(|(_, button)| *button)
+                .collect(),
+            // gilrs's y axis points up
+            stick: vec2(
+                gamepad.value(Axis::LeftStickX),
+                -gamepad.value(Axis::LeftStickY),
+            ),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use macroquad::prelude::*;
+
+    use super::{Button, GamepadBackend, GamepadState};
+
+    // Implemented by the `wasm/static/gamepad.js` plugin, using the browser gamepad API.
+    extern "C" {
+        fn gamepad_count() -> u32;
+        fn gamepad_connected(index: u32) -> u32;
+        fn gamepad_button(index: u32, button: u32) -> u32;
+        fn gamepad_axis(index: u32, axis: u32) -> f32;
+    }
+
+    /// Version of the plugin, checked by `gl.js` against the one in `gamepad.js`.
+    #[no_mangle]
+    pub extern "C" fn gamepad_crate_version() -> u32 {
+        1 << 16 // 0.1.0
+    }
+
+    /// Reads the gamepads of the browser, following the standard mapping.
+    pub struct PlatformGamepads;
+
+    impl PlatformGamepads {
+        pub const fn new() -> Self {
+            Self
+        }
+    }
+
+    impl GamepadBackend for PlatformGamepads {
+        fn poll(&mut self) -> Vec<(usize, GamepadState)> {
+            let buttons = [
+                (0, Button::South),
+                (1, Button::East),
+                (2, Button::West),
+                (3, Button::North),
+                (9, Button::Start),
+                (12, Button::DPadUp),
+                (13, Button::DPadDown),
+                (14, Button::DPadLeft),
+                (15, Button::DPadRight),
+            ];
+
+            // SAFETY: the functions are provided by the plugin and only read the browser state.
+            unsafe {
+                (0..gamepad_count())
+                    .filter(|index| gamepad_connected(*index) != 0)
+                    .map(|index| {
+                        let state = GamepadState {
+                            buttons: buttons
+                                .iter()
+                                .filter(|(code, _)| gamepad_button(index, *code) != 0)
+                                .map(|(_, button)| *button)
+                                .collect(),
+                            stick: vec2(gamepad_axis(index, 0), gamepad_axis(index, 1)),
+                        };
+                        (index as usize, state)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    /// A backend whose gamepads are controlled by the tests.
+    #[derive(Clone, Default)]
+    struct FakeGamepads(Rc<RefCell<Vec<(usize, GamepadState)>>>);
+
+    impl FakeGamepads {
+        fn set(&self, id: usize, buttons: &[Button], stick: Vec2) {
+            let mut gamepads = self.0.borrow_mut();
+            gamepads.retain(|(gamepad_id, _)| *gamepad_id != id);
+            gamepads.push((
+                id,
+                GamepadState {
+                    buttons: buttons.to_vec(),
+                    stick,
+                },
+            ));
+        }
+
+        fn unplug(&self, id: usize) {
+            self.0
+                .borrow_mut()
+                .retain(|(gamepad_id, _)| *gamepad_id != id);
+        }
+    }
+
+    impl GamepadBackend for FakeGamepads {
+        fn poll(&mut self) -> Vec<(usize, GamepadState)> {
+            self.0.borrow().clone()
+        }
+    }
+
+    fn gamepads() -> (FakeGamepads, Gamepads) {
+        let fake = FakeGamepads::default();
+        let gamepads = Gamepads::with_backend(Box::new(fake.clone()));
+        (fake, gamepads)
+    }
+
+    #[test]
+    fn face_buttons_trigger_actions_once() {
+        let (fake, mut gamepads) = gamepads();
+
+        fake.set(0, &[Button::South], Vec2::zero());
+        gamepads.update();
+        assert!(gamepads.is_pressed(Action::Attack));
+        assert!(gamepads.is_pressed(Action::Confirm));
+        assert!(!gamepads.is_down(Action::Pause));
+
+        gamepads.update();
+        assert!(gamepads.is_down(Action::Attack));
+        assert!(!gamepads.is_pressed(Action::Attack));
+    }
+
+    #[test]
+    fn stick_is_ignored_inside_the_dead_zone() {
+        let (fake, mut gamepads) = gamepads();
+
+        fake.set(0, &[], vec2(0.2, -0.2));
+        gamepads.update();
+        assert!(Action::iter().all(|action| !gamepads.is_down(action)));
+
+        fake.set(0, &[], vec2(0.3, -0.8));
+        gamepads.update();
+        assert!(gamepads.is_pressed(Action::Up));
+        assert!(!gamepads.is_down(Action::Right));
+    }
+
+    #[test]
+    fn stick_and_dpad_press_the_same_direction_once() {
+        let (fake, mut gamepads) = gamepads();
+
+        fake.set(0, &[Button::DPadLeft], Vec2::zero());
+        gamepads.update();
+        fake.set(0, &[Button::DPadLeft], vec2(-1., 0.));
+        gamepads.update();
+
+        assert!(gamepads.is_down(Action::Left));
+        assert!(!gamepads.is_pressed(Action::Left));
+    }
+
+    #[test]
+    fn hot_plugging_is_detected() {
+        let (fake, mut gamepads) = gamepads();
+
+        gamepads.update();
+        assert_eq!(gamepads.connected(), 0);
+
+        fake.set(3, &[], Vec2::zero());
+        gamepads.update();
+        assert_eq!(gamepads.connected(), 1);
+        assert!(!gamepads.just_disconnected());
+
+        fake.unplug(3);
+        gamepads.update();
+        assert!(gamepads.just_disconnected());
+        assert_eq!(gamepads.connected(), 0);
+    }
+}
@@ -0,0 +1,112 @@
+//! A watcher of the resource files, reloading them while the game is running.
+//!
+//! It is only enabled in the native debug builds reading the files from the disk, the
+//! release, browser and embedded builds never reporting any change.
+
+use crate::{assets::Assets, resources::Animations};
+
+/// The kinds of files changed since the last check.
+#[derive(Default)]
+pub struct Changes {
+    /// Never set by the watcher, the settings being saved by the game itself.
+    pub settings: bool,
+    pub textures: bool,
+    pub tuning: bool,
+}
+
+impl Changes {
+    /// Every kind of file, to reload them all at once.
+    #[cfg_attr(not(debug_assertions), allow(dead_code))] // reloaded from the console
+    pub const fn all() -> Self {
+        Self {
+            settings: true,
+            textures: true,
+            tuning: true,
+        }
+    }
+}
+
+/// Polls the modification times of the textures, of the animation manifest and of the
+/// tuning file.
+#[derive(Default)]
+pub struct Watcher {
+    /// Last modification time of each watched file.
+    #[cfg(all(
+        debug_assertions,
+        not(target_arch = "wasm32"),
+        not(feature = "embedded-assets")
+    ))]
+    modified: std::collections::HashMap<String, Option<std::time::SystemTime>>,
+    /// Real time of the last check.
+    #[cfg(all(
+        debug_assertions,
+        not(target_arch = "wasm32"),
+        not(feature = "embedded-assets")
+    ))]
+    last_poll: f64,
+}
+
+#[cfg(all(
+    debug_assertions,
+    not(target_arch = "wasm32"),
+    not(feature = "embedded-assets")
+))]
+impl Watcher {
+    /// Real time, in seconds, between two checks of the files.
+    const POLL_INTERVAL: f64 = 0.5;
+
+    /// Returns the files changed since the last call, checking them at most every
+    /// `POLL_INTERVAL` seconds.
+    pub fn poll(&mut self, assets: &Assets, animations: &Animations) -> Changes {
+        let now = macroquad::time::get_time();
+        if now - self.last_poll < Self::POLL_INTERVAL {
+            return Changes::default();
+        }
+        self.last_poll = now;
+
+        let texture_files = assets.texture_files();
+        let textures = texture_files
+            .iter()
+            .map(|file| assets.path(file))
+            .chain(animations.files().iter().cloned());
+        // every file is checked, to record all the new times at once
+        let changed_textures = textures.filter(|path| self.check(path)).count();
+
+        Changes {
+            settings: false,
+            textures: changed_textures > 0,
+            tuning: self.check(&assets.path(&assets.manifest.tuning)),
+        }
+    }
+
+    /// Records the modification time of the file, returning true if it changed since
+    /// the previous check.
+    ///
+    /// The files checked for the first time are not considered changed.
+    fn check(&mut self, path: &str) -> bool {
+        let modified = std::fs::metadata(path)
+            .and_then(|file| file.modified())
+            .ok();
+
+        self.modified
+            .insert(path.to_owned(), modified)
+            .is_some_and(|previous| previous != modified)
+    }
+}
+
+#[cfg(not(all(
+    debug_assertions,
+    not(target_arch = "wasm32"),
+    not(feature = "embedded-assets")
+)))]
+impl Watcher {
+    /// Never reports any change, the files not being watched in this build.
+    #[allow(clippy::unused_self)]
+    pub const fn poll(&self, _: &Assets, _: &Animations) -> Changes {
+        Changes {
+            settings: false,
+            textures: false,
+            tuning: false,
+        }
+    }
+}
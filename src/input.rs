@@ -0,0 +1,289 @@
+//! The input actions of the game and the keys bound to them.
+
+use std::{collections::BTreeMap, str::Chars};
+
+use macroquad::prelude::*;
+use nanoserde::{DeRon, DeRonErr, DeRonState, SerRon, SerRonState};
+
+use crate::{
+    direction::Direction,
+    gamepad::Gamepads,
+    pointer::{Gesture, Pointer},
+};
+
+/// Maximum number of keys that can be bound to a single action.
+const MAX_KEYS_PER_ACTION: usize = 3;
+
+/// An action of the player, triggered by any of its bound keys.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, SerRon, DeRon)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Attack,
+    Pause,
+    Confirm,
+    Mute,
+    Fullscreen,
+}
+
+#[allow(clippy::enum_glob_use)]
+use Action::*;
+
+impl Action {
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [
+            Up, Down, Left, Right, Attack, Pause, Confirm, Mute, Fullscreen,
+        ]
+        .iter()
+        .copied()
+    }
+
+    /// Returns the keys bound to the action by default.
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Up => vec![KeyCode::Up, KeyCode::W],
+            Down => vec![KeyCode::Down, KeyCode::S],
+            Left => vec![KeyCode::Left, KeyCode::A],
+            Right => vec![KeyCode::Right, KeyCode::D],
+            Attack => vec![KeyCode::Space],
+            Pause => vec![KeyCode::Escape, KeyCode::P],
+            Confirm => vec![KeyCode::Enter, KeyCode::Space],
+            Mute => vec![KeyCode::M],
+            Fullscreen => vec![KeyCode::F11],
+        }
+    }
+}
+
+impl From<Direction> for Action {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Up,
+            Direction::Right => Right,
+            Direction::Down => Down,
+            Direction::Left => Left,
+        }
+    }
+}
+
+/// How the player faces and attacks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, SerRon, DeRon)]
+pub enum ControlScheme {
+    /// The directions face and the attack strikes where the player is facing.
+    #[default]
+    Classic,
+    /// Each direction press faces and strikes at once.
+    DirectStrike,
+}
+
+impl ControlScheme {
+    /// Returns the other control scheme.
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Classic => Self::DirectStrike,
+            Self::DirectStrike => Self::Classic,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::DirectStrike => "Direct strike",
+        }
+    }
+}
+
+/// The keys bound to each action.
+#[derive(Clone)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    /// Returns the keys bound to the action.
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if any key of the action is being pressed.
+    pub fn is_down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_down(*key))
+    }
+
+    /// Returns true if any key of the action has just been pressed.
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_pressed(*key))
+    }
+
+    /// Binds the key to the action, replacing its oldest key if it has too many.
+    ///
+    /// The key is removed from the other actions, unless it is the only key of one of
+    /// them: the binding is then refused and that action returned.
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), Action> {
+        let only_key = self
+            .keys
+            .iter()
+            .find(|(other, keys)| **other != action && keys.as_slice() == [key]);
+        if let Some((other, _)) = only_key {
+            return Err(*other);
+        }
+
+        for (other, keys) in &mut self.keys {
+            if *other != action {
+                keys.retain(|bound| *bound != key);
+            }
+        }
+
+        let keys = self.keys.entry(action).or_default();
+        keys.retain(|bound| *bound != key);
+        keys.push(key);
+
+        if keys.len() > MAX_KEYS_PER_ACTION {
+            keys.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Restores the default keys of the action.
+    pub fn reset(&mut self, action: Action) {
+        self.keys.insert(action, action.default_keys());
+    }
+
+    /// Returns the names of the keys bound to the action, separated by commas.
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<_> = self.keys(action).iter().map(|key| key_name(*key)).collect();
+        names.join(", ")
+    }
+}
+
+/// The actions triggered during the frame by the keyboard, the gamepads and the pointer.
+#[derive(Clone, Copy)]
+pub struct Input<'a> {
+    bindings: &'a Bindings,
+    gamepads: &'a Gamepads,
+    pointer: &'a Pointer,
+}
+
+impl<'a> Input<'a> {
+    pub const fn new(bindings: &'a Bindings, gamepads: &'a Gamepads, pointer: &'a Pointer) -> Self {
+        Self {
+            bindings,
+            gamepads,
+            pointer,
+        }
+    }
+
+    /// Returns true if the action is being pressed.
+    pub fn is_down(self, action: Action) -> bool {
+        self.bindings.is_down(action) || self.gamepads.is_down(action)
+    }
+
+    /// Returns true if the action has just been pressed.
+    ///
+    /// Tapping the pointer confirms and swiping it presses the direction.
+    pub fn is_pressed(self, action: Action) -> bool {
+        let gesture = match self.pointer.gesture() {
+            Some(Gesture::Tap(_)) => action == Confirm,
+            Some(Gesture::Swipe(direction)) => action == Action::from(direction),
+            None => false,
+        };
+        gesture || self.bindings.is_pressed(action) || self.gamepads.is_pressed(action)
+    }
+
+    /// Returns the directions whose action has just been pressed, by order of priority.
+    ///
+    /// When several directions are pressed during the same frame, the priority follows
+    /// the order of [`Direction::iter`]: up, right, down then left.
+    pub fn pressed_directions(self) -> impl Iterator<Item = Direction> + 'a {
+        Direction::iter().filter(move |dir| self.is_pressed(Action::from(*dir)))
+    }
+
+    /// Returns the direction with the highest priority among the ones just pressed.
+    pub fn pressed_direction(self) -> Option<Direction> {
+        self.pressed_directions().next()
+    }
+}
+
+/// The key bindings as they are saved in the settings, using the key names.
+#[derive(SerRon, DeRon)]
+pub struct SavedBindings {
+    keys: BTreeMap<Action, Vec<String>>,
+}
+
+impl From<&Bindings> for SavedBindings {
+    fn from(bindings: &Bindings) -> Self {
+        let keys = bindings
+            .keys
+            .iter()
+            .map(|(action, keys)| (*action, keys.iter().map(|key| key_name(*key)).collect()))
+            .collect();
+
+        Self { keys }
+    }
+}
+
+impl From<&SavedBindings> for Bindings {
+    /// Restores the saved bindings, keeping the default keys of the missing actions.
+    fn from(saved: &SavedBindings) -> Self {
+        let mut bindings = Self::default();
+
+        for (action, names) in &saved.keys {
+            let keys: Vec<_> = names.iter().filter_map(|name| parse_key(name)).collect();
+
+            if !keys.is_empty() {
+                bindings.keys.insert(*action, keys);
+            }
+        }
+        bindings
+    }
+}
+
+impl SerRon for Bindings {
+    fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+        SavedBindings::from(self).ser_ron(d, s);
+    }
+}
+
+impl DeRon for Bindings {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        SavedBindings::de_ron(s, i).map(|saved| Self::from(&saved))
+    }
+}
+
+/// All the keys that can be bound to an action.
+#[rustfmt::skip]
+const KEYS: [KeyCode; 105] = {
+    #[allow(clippy::enum_glob_use)]
+    use KeyCode::*;
+    [
+        Space, Apostrophe, Comma, Minus, Period, Slash, Key0, Key1, Key2, Key3, Key4, Key5, Key6,
+        Key7, Key8, Key9, Semicolon, Equal, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S,
+        T, U, V, W, X, Y, Z, LeftBracket, Backslash, RightBracket, GraveAccent, Escape, Enter, Tab,
+        Backspace, Insert, Delete, Right, Left, Down, Up, PageUp, PageDown, Home, End, CapsLock,
+        ScrollLock, NumLock, PrintScreen, Pause, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpDecimal, KpDivide, KpMultiply,
+        KpSubtract, KpAdd, KpEnter, KpEqual, LeftShift, LeftControl, LeftAlt, LeftSuper, RightShift,
+        RightControl, RightAlt, RightSuper, Menu,
+    ]
+};
+
+/// Returns the name of the key, as displayed and saved in the settings.
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+/// Returns the key with the given name, if it can be bound.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    KEYS.iter().copied().find(|key| key_name(*key) == name)
+}
@@ -1,43 +1,53 @@
-use macroquad::prelude::*;
-
-use crate::resources::Textures;
+use crate::resources::{Sprite, Textures};
 
 pub struct LifeBar {
     max_lives: u32,
     lives: u32,
-    full_texture: Texture2D,
-    empty_texture: Texture2D,
+    full_heart: Sprite,
+    empty_heart: Sprite,
 }
 
 impl LifeBar {
-    pub const fn new(max_lives: u32, textures: &Textures) -> Self {
+    pub fn new(max_lives: u32, textures: &Textures) -> Self {
         Self {
             max_lives,
             lives: max_lives,
-            full_texture: textures.heart,
-            empty_texture: textures.empty_heart,
+            full_heart: textures.heart.clone(),
+            empty_heart: textures.empty_heart.clone(),
         }
     }
 
+    /// Uses the reloaded heart textures.
+    pub fn refresh_textures(&mut self, textures: &Textures) {
+        self.full_heart = textures.heart.clone();
+        self.empty_heart = textures.empty_heart.clone();
+    }
+
     /// Draws full hearts for remaining lives and empty hearts for lost ones.
     pub fn draw(&self) {
         for index in 0..self.max_lives {
-            let texture = if index < self.lives {
-                self.full_texture
+            let heart = if index < self.lives {
+                &self.full_heart
             } else {
-                self.empty_texture
+                &self.empty_heart
             };
 
             let margin: f32 = 2.0;
-            let x = (index as f32).mul_add(texture.width(), margin);
-            draw_texture(texture, x, 0., WHITE);
+            let x = (index as f32).mul_add(heart.width(), margin);
+            heart.draw(x, 0.);
         }
     }
 
-    pub fn decrement(&mut self) {
+    pub const fn decrement(&mut self) {
         self.lives = self.lives.saturating_sub(1);
     }
 
+    /// Sets the remaining lives, up to the maximum.
+    #[cfg_attr(not(debug_assertions), allow(dead_code))] // set from the console
+    pub fn set_lives(&mut self, lives: u32) {
+        self.lives = lives.min(self.max_lives);
+    }
+
     pub const fn is_empty(&self) -> bool {
         self.lives == 0
     }
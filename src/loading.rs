@@ -0,0 +1,124 @@
+//! The screen shown while the assets are loaded, when the game starts.
+
+use std::future::Future;
+
+use macroquad::prelude::*;
+
+use crate::{resources::Fonts, screen_drawer::ScreenDrawer, GAME_HEIGHT, GAME_WIDTH};
+
+const BAR_WIDTH: f32 = 150.;
+const BAR_HEIGHT: f32 = 6.;
+/// Largest width of the lines of an error.
+const ERROR_WIDTH: f32 = GAME_WIDTH - 20.;
+
+/// A progress bar advancing as each group of assets is loaded.
+///
+/// The font of the game being one of these assets, the texts use the default font.
+pub struct LoadingScreen<'a> {
+    screen_drawer: &'a ScreenDrawer,
+    steps: usize,
+    done: usize,
+}
+
+impl<'a> LoadingScreen<'a> {
+    /// Creates the screen of a loading in the given number of steps.
+    pub const fn new(screen_drawer: &'a ScreenDrawer, steps: usize) -> Self {
+        Self {
+            screen_drawer,
+            steps,
+            done: 0,
+        }
+    }
+
+    /// Shows the progress while a group of assets is loaded.
+    ///
+    /// The game cannot start without its assets, so an error is shown instead of
+    /// returning, until the window is closed.
+    pub async fn load<T>(
+        &mut self,
+        label: &str,
+        loading: impl Future<Output = Result<T, String>>,
+    ) -> T {
+        debug_assert!(self.done < self.steps, "More steps loaded than announced");
+        self.draw_progress(label);
+        next_frame().await; // shown before the loading blocks the frame
+
+        match loading.await {
+            Ok(loaded) => {
+                self.done += 1;
+                loaded
+            }
+            Err(err) => self.show_error(&err).await,
+        }
+    }
+
+    fn draw_progress(&self, label: &str) {
+        let progress = self.done as f32 / self.steps as f32;
+        let x = (GAME_WIDTH - BAR_WIDTH) / 2.;
+        let y = (GAME_HEIGHT - BAR_HEIGHT) / 2.;
+
+        self.screen_drawer.draw_scaled(|| {
+            clear_background(BLACK);
+
+            let params = TextParams {
+                font_size: 16,
+                ..TextParams::default()
+            };
+            let text = format!("Loading {label}");
+            Fonts::draw_centered(&text, GAME_WIDTH / 2., y - 10., params);
+
+            draw_rectangle_lines(x - 2., y - 2., BAR_WIDTH + 4., BAR_HEIGHT + 4., 1., WHITE);
+            draw_rectangle(x, y, BAR_WIDTH * progress, BAR_HEIGHT, WHITE);
+        });
+    }
+
+    async fn show_error(&self, error: &str) -> ! {
+        eprintln!("{error}");
+
+        let params = TextParams {
+            font_size: 12,
+            ..TextParams::default()
+        };
+        let lines = wrap(error, params);
+
+        loop {
+            self.screen_drawer.draw_scaled(|| {
+                clear_background(BLACK);
+
+                let title = TextParams {
+                    font_size: 16,
+                    color: RED,
+                    ..params
+                };
+                Fonts::draw_centered("Cannot load the game", GAME_WIDTH / 2., 40., title);
+
+                for (index, line) in lines.iter().enumerate() {
+                    let y = (index as f32).mul_add(14., 70.);
+                    draw_text_ex(line, 10., y, params);
+                }
+            });
+
+            next_frame().await;
+        }
+    }
+}
+
+/// Splits the text into lines fitting in the error width, anywhere in the words since
+/// the paths are long.
+fn wrap(text: &str, params: TextParams) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for character in text.chars() {
+        line.push(character);
+
+        let width = measure_text(&line, None, params.font_size, params.font_scale).width;
+        if width > ERROR_WIDTH {
+            line.pop();
+            lines.push(std::mem::take(&mut line));
+            line.push(character);
+        }
+    }
+    lines.push(line);
+    lines
+}
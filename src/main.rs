@@ -11,26 +11,66 @@
     clippy::future_not_send,
     clippy::cast_precision_loss,
     clippy::cast_possible_truncation,
-    clippy::eval_order_dependence
+    clippy::mixed_read_write_in_expression,
+    clippy::multiple_crate_versions
 )]
 
+use assets::Assets;
+#[cfg(debug_assertions)]
+use console::{Console, Context};
+use debug::DebugOverlay;
 use enemy::Enemy;
-use life_bar::LifeBar;
+use gamepad::Gamepads;
+use hot_reload::{Changes, Watcher};
+use input::{key_name, Action, ControlScheme, Input};
+use loading::LoadingScreen;
 use macroquad::{prelude::*, rand::srand};
-use player::Player;
-use resources::{Animations, Fonts, Textures};
-use screen_drawer::ScreenDrawer;
-use timers::{Cooldown, Timer};
+use menu::Menu;
+use mixer::Mixer;
+use options::OptionItem;
+use particles::{BLOOD, DUST, SPARKS};
+use pointer::Pointer;
+use resources::{Animations, Effect, Fonts, Sounds, Textures, Track};
+use run::Run;
+use screen_drawer::{set_fullscreen, ScreenDrawer, FULLSCREEN_AT_RUNTIME};
+use settings::Settings;
+use timers::{advance_game_time, game_time, scaled_frame_time};
+use tuning::Tuning;
 
 mod animation;
+mod animator;
+mod aseprite;
+mod assets;
+mod camera;
 mod character;
+#[cfg(debug_assertions)]
+mod commands;
+#[cfg(debug_assertions)]
+mod console;
+mod debug;
 mod direction;
 mod enemy;
+mod gamepad;
+mod hot_reload;
+mod input;
 mod life_bar;
+mod loading;
+mod manifest;
+mod menu;
+mod mixer;
+mod music;
+mod options;
+mod packer;
+mod palette;
+mod particles;
 mod player;
+mod pointer;
 mod resources;
+mod run;
 mod screen_drawer;
+mod settings;
 mod timers;
+mod tuning;
 
 /// Width of the game.
 pub const GAME_WIDTH: f32 = 250.;
@@ -38,14 +78,8 @@ pub const GAME_WIDTH: f32 = 250.;
 pub const GAME_HEIGHT: f32 = 250.;
 /// Margin for the score text.
 pub const MARGIN: f32 = 4.;
-/// Movement speed of enemies.
-pub const ENEMY_SPEED: f32 = 40.;
-/// Initial delay between each enemy spawn.
-pub const INITIAL_SPAWN_DELAY: f64 = 1.;
-/// Duration of the cooldown between attacks.
-pub const ATTACK_COOLDOWN: f64 = 0.3;
-/// Initial amount of life the player has.
-pub const LIVES: u32 = 5;
+/// Width and height of the hit box of enemies.
+pub const ENEMY_SIZE: f32 = 16.;
 
 #[macroquad::main(window_conf)]
 async fn main() {
@@ -53,126 +87,540 @@ async fn main() {
 }
 
 struct Game {
+    assets: Assets,
     textures: Textures,
     fonts: Fonts,
     animations: Animations,
+    mixer: Mixer,
     screen_drawer: ScreenDrawer,
-    max_score: u32,
+    settings: Settings,
+    tuning: Tuning,
+    watcher: Watcher,
+    gamepads: Gamepads,
+    pointer: Pointer,
+    debug: DebugOverlay,
+    /// The developer console, only in the debug builds since its commands are cheats.
+    #[cfg(debug_assertions)]
+    console: Console,
 }
 
 impl Game {
+    /// Number of groups of assets loaded by `load`, each one a step of the loading
+    /// screen.
+    const LOADING_STEPS: usize = 5;
+
+    /// Loads the assets while showing their progress, or the error preventing the game
+    /// from starting.
     async fn load() -> Self {
-        let textures = Textures::load().await;
+        let settings = Settings::load();
+        let screen_drawer = ScreenDrawer::new(GAME_WIDTH, GAME_HEIGHT, settings.scaling);
+
+        let mut loading = LoadingScreen::new(&screen_drawer, Self::LOADING_STEPS);
+        let assets = loading.load("manifest", Assets::load()).await;
+        let textures = loading.load("textures", Textures::load(&assets)).await;
+        let fonts = loading.load("font", Fonts::load(&assets)).await;
+        let animations = loading.load("animations", Animations::load(&assets)).await;
+        let sounds = loading.load("sounds", Sounds::load(&assets)).await;
+
+        #[cfg(debug_assertions)]
+        let console = {
+            let mut console = Console::default();
+            commands::register(&mut console);
+            console
+        };
+
         Self {
-            fonts: Fonts::load().await,
-            animations: Animations::new(&textures),
+            fonts,
+            animations,
             textures,
-            screen_drawer: ScreenDrawer::new(GAME_WIDTH, GAME_HEIGHT),
-            max_score: 0,
+            mixer: Mixer::new(sounds, settings.volumes),
+            screen_drawer,
+            settings,
+            tuning: Tuning::load(&assets),
+            assets,
+            watcher: Watcher::default(),
+            gamepads: Gamepads::new(),
+            pointer: Pointer::default(),
+            debug: DebugOverlay::default(),
+            #[cfg(debug_assertions)]
+            console,
+        }
+    }
+
+    /// Updates the input devices and the sounds, to be called at the start of each frame.
+    ///
+    /// The global actions, such as muting, are skipped while `listening` for a key to
+    /// bind.
+    fn begin_frame(&mut self, listening: bool) {
+        self.gamepads.update();
+        self.pointer.update(&self.screen_drawer);
+        self.debug.update(self.console_open());
+
+        if self.console_open() || listening {
+            // the keys are typed in the console or bound
+        } else if self.input().is_pressed(Action::Mute) {
+            self.settings.volumes.muted = !self.settings.volumes.muted;
+            self.settings.save();
+        } else if self.input().is_pressed(Action::Fullscreen) {
+            self.settings.fullscreen = !self.settings.fullscreen;
+            set_fullscreen(self.settings.fullscreen);
+            self.settings.save();
         }
+        self.mixer.update(self.settings.volumes);
+    }
+
+    /// Returns true while the console is open, which never happens in the release builds.
+    #[cfg_attr(not(debug_assertions), allow(clippy::unused_self))]
+    const fn console_open(&self) -> bool {
+        #[cfg(debug_assertions)]
+        return self.console.is_open();
+
+        #[cfg(not(debug_assertions))]
+        false
+    }
+
+    /// Returns the input of the frame, once updated.
+    const fn input(&self) -> Input<'_> {
+        Input::new(&self.settings.bindings, &self.gamepads, &self.pointer)
     }
 
     async fn start(&mut self) {
         loop {
             self.menu().await;
-            let score = self.game().await;
+            let run = self.game().await;
 
-            self.max_score = self.max_score.max(score);
+            if !run.cheated && run.score > self.settings.best_score {
+                self.settings.best_score = run.score;
+                self.settings.save();
+            }
         }
     }
 
-    async fn menu(&self) {
+    async fn menu(&mut self) {
+        let items = ["Start".to_owned(), "Options".to_owned()];
+        let mut menu = Menu::new(items.len());
+
+        self.mixer.play_music(Track::Menu);
+
         loop {
-            if is_key_pressed(KeyCode::Space) {
-                return;
+            self.begin_frame(false);
+            let input = self.input();
+            menu.navigate(input);
+
+            if input.is_pressed(Action::Confirm) {
+                match menu.selected() {
+                    0 => return,
+                    _ => self.options().await,
+                }
             }
 
             self.screen_drawer.draw_scaled(|| {
-                draw_texture(self.textures.background, 0., 0., WHITE);
+                self.textures.background.draw(0., 0.);
 
                 let x = GAME_WIDTH / 2.;
                 let y = GAME_HEIGHT / 2.;
 
                 let title = "Arcade knight";
-                let score = &format!("Max score: {}", self.max_score);
-                let start = "Press space to start";
+                let score = &format!("Max score: {}", self.settings.best_score);
 
                 Fonts::draw_centered(title, x, y, self.fonts.sized(20));
                 Fonts::draw_centered(score, x, y + 15., self.fonts.sized(8));
-                Fonts::draw_centered(start, x, y + 50., self.fonts.sized(8));
+                menu.draw(&items, x, y + 50., &self.fonts);
+
+                if self.gamepads.connected() > 0 {
+                    let gamepads = "Gamepad connected";
+                    Fonts::draw_centered(gamepads, x, GAME_HEIGHT - 20., self.fonts.sized(8));
+                }
             });
 
             next_frame().await;
         }
     }
 
-    async fn game(&self) -> u32 {
-        srand(get_time().to_bits());
-
-        let mut score: u32 = 0;
-        let mut life_bar = LifeBar::new(LIVES, &self.textures);
+    /// Shows the options and lets the player change them.
+    async fn options(&mut self) {
+        let items: Vec<_> = OptionItem::iter().collect();
+        let mut menu = Menu::new(items.len());
 
-        let mut player = Player::new(12., 12., &self.textures);
-        let mut enemies: Vec<Enemy> = Vec::new();
-
-        let mut attack_cooldown = Cooldown::from_seconds(ATTACK_COOLDOWN);
-        let mut enemy_spawner = Timer::from_seconds(INITIAL_SPAWN_DELAY);
+        next_frame().await; // skip the confirm press that opened the screen
 
         loop {
-            player.update_direction();
-            player.animate_attack();
+            self.begin_frame(false);
+            let input = self.input();
+            let item = items[menu.selected()];
+            let steps = match (
+                input.is_pressed(Action::Left),
+                input.is_pressed(Action::Right),
+            ) {
+                (true, false) => -1.,
+                (false, true) => 1.,
+                _ => 0.,
+            };
 
-            if is_key_pressed(KeyCode::Space) && attack_cooldown.available() {
-                attack_cooldown.start();
-                player.start_attack(&self.animations);
-            }
-            if enemy_spawner.tick_and_finished() {
-                enemies.push(Enemy::new_random(16., 16., &self.animations));
+            if input.is_pressed(Action::Pause) {
+                break;
+            } else if input.is_pressed(Action::Confirm) {
+                match item {
+                    OptionItem::Controls => self.controls().await,
+                    OptionItem::Back => break,
+                    _ => self.change_option(item, 0.),
+                }
+            } else if steps != 0. {
+                self.change_option(item, steps);
+            } else {
+                menu.navigate(input);
             }
 
-            for enemy in &mut enemies {
-                enemy.move_and_animate();
+            let player_skin = self.player_skin();
+            let labels: Vec<_> = items
+                .iter()
+                .map(|item| item.label(&self.settings, player_skin))
+                .collect();
+
+            self.screen_drawer.draw_scaled(|| {
+                self.textures.background.draw(0., 0.);
+
+                let x = GAME_WIDTH / 2.;
+                let hint = match items[menu.selected()] {
+                    OptionItem::Fullscreen if !FULLSCREEN_AT_RUNTIME => "Applies on restart",
+                    _ => "Left / Right: change the value",
+                };
 
-                if player.kill(enemy) {
-                    score += 10;
-                    attack_cooldown.reset();
-                    enemy_spawner.delay = 1.0 / get_time().mul_add(0.1, 0.5);
-                    enemy.alive = false;
+                Fonts::draw_centered("Options", x, 40., self.fonts.sized(20));
+                menu.draw(&labels, x, 70., &self.fonts);
+                Fonts::draw_centered(hint, x, GAME_HEIGHT - 20., self.fonts.sized(8));
+            });
+
+            next_frame().await;
+        }
+
+        self.settings.save();
+        next_frame().await; // skip the press that closed the screen
+    }
+
+    /// Changes the option and applies the display settings.
+    fn change_option(&mut self, item: OptionItem, steps: f32) {
+        item.change(&mut self.settings, steps);
+        self.screen_drawer.scaling = self.settings.scaling;
+
+        if item == OptionItem::Fullscreen {
+            set_fullscreen(self.settings.fullscreen);
+        }
+        if item == OptionItem::Skin {
+            let skins = self.unlocked_player_skins();
+            let current = skins.iter().position(|skin| *skin == self.player_skin());
+            let next = match current {
+                Some(index) if steps < 0. => (index + skins.len() - 1) % skins.len(),
+                Some(index) => (index + 1) % skins.len(),
+                None => 0,
+            };
+            self.settings.skin = skins[next].to_owned();
+        }
+    }
+
+    fn unlocked_player_skins(&self) -> Vec<&str> {
+        self.animations
+            .unlocked_skins("player", self.settings.best_score)
+    }
+
+    /// Returns the chosen skin of the player, or the default one if it is not unlocked.
+    fn player_skin(&self) -> &str {
+        let skins = self.unlocked_player_skins();
+
+        skins
+            .iter()
+            .find(|skin| **skin == self.settings.skin)
+            .map_or("player", |skin| skin)
+    }
+
+    /// Shows the key bindings of each action and lets the player rebind them
+    /// and choose the control scheme.
+    async fn controls(&mut self) {
+        let actions: Vec<_> = Action::iter().collect();
+        let scheme_index = actions.len();
+        let mut menu = Menu::new(actions.len() + 2); // actions, scheme and "Back"
+        let mut listening: Option<Action> = None;
+        let mut refused: Option<(KeyCode, Action)> = None;
+
+        next_frame().await; // skip the confirm press that opened the screen
+
+        loop {
+            self.begin_frame(listening.is_some());
+            let input = self.input();
+            let selected = actions.get(menu.selected()).copied();
+
+            if let Some(action) = listening {
+                // Escape cancels the rebinding rather than being bound
+                if let Some(key) = get_last_key_pressed() {
+                    if key != KeyCode::Escape {
+                        let bound = self.settings.bindings.bind(action, key);
+                        refused = bound.err().map(|other| (key, other));
+                    }
+                    listening = None;
                 }
-                if enemy.character.collide(&player.character) {
-                    life_bar.decrement();
-                    enemy.alive = false;
+            } else if input.is_pressed(Action::Pause) {
+                break;
+            } else if input.is_pressed(Action::Confirm) {
+                match selected {
+                    Some(action) => {
+                        listening = Some(action);
+                        refused = None;
+                    }
+                    None if menu.selected() == scheme_index => {
+                        self.settings.control_scheme = self.settings.control_scheme.toggled();
+                    }
+                    None => break,
                 }
+            } else if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+                if let Some(action) = selected {
+                    self.settings.bindings.reset(action);
+                }
+            } else {
+                menu.navigate(input);
             }
 
-            enemies.retain(|enemy| enemy.alive);
+            let bindings = &self.settings.bindings;
+            let mut items: Vec<_> = actions
+                .iter()
+                .map(|action| match listening {
+                    Some(listened) if listened == *action => format!("{action:?}: press a key"),
+                    _ => format!("{action:?}: {}", bindings.describe(*action)),
+                })
+                .collect();
+            items.push(format!("Scheme: {}", self.settings.control_scheme.name()));
+            items.push("Back".to_owned());
 
             self.screen_drawer.draw_scaled(|| {
-                clear_background(LIME);
-                draw_texture(self.textures.background, 0., 0., WHITE);
-                let Rect { x, y, w, h } = player.slash_attack();
-                draw_rectangle(x, y, w, h, SKYBLUE);
-                player.draw();
-                enemies.iter().for_each(Enemy::draw);
-                life_bar.draw();
-
-                if cfg!(debug_assertions) {
-
-                    player.character.draw_hit_box();
-                    enemies.iter().for_each(|e| e.character.draw_hit_box());
-                }
+                self.textures.background.draw(0., 0.);
+
+                let x = GAME_WIDTH / 2.;
+                let hint = match refused {
+                    Some((key, other)) => format!("{} is the only key of {other:?}", key_name(key)),
+                    None if listening.is_some() => "Escape: cancel".to_owned(),
+                    None => "Delete: restore default keys".to_owned(),
+                };
 
-                let score = &format!("Score: {}", score);
-                Fonts::draw_left(score, GAME_WIDTH - MARGIN, MARGIN, self.fonts.sized(8));
+                Fonts::draw_centered("Controls", x, 40., self.fonts.sized(20));
+                menu.draw(&items, x, 70., &self.fonts);
+                Fonts::draw_centered(&hint, x, GAME_HEIGHT - 20., self.fonts.sized(8));
             });
 
-            if life_bar.is_empty() {
-                return score;
+            next_frame().await;
+        }
+
+        self.settings.save();
+        next_frame().await; // skip the press that closed the screen
+    }
+
+    /// Plays a run until the player has no more lives, and returns it.
+    async fn game(&mut self) -> Run {
+        srand(get_time().to_bits());
+
+        let player_skin = self.player_skin();
+        let mut run = Run::new(&self.textures, &self.animations, &self.tuning, player_skin);
+
+        self.mixer.play_music(Track::Game);
+
+        loop {
+            #[cfg(debug_assertions)]
+            let reload = self.update_console(&mut run);
+            #[cfg(not(debug_assertions))]
+            let reload = false;
+            self.begin_frame(false);
+
+            self.hot_reload(&mut run, reload).await;
+            if self.console_open() {
+                // the game waits while commands are typed
+            } else if self.input().is_pressed(Action::Pause) {
+                run.paused = !run.paused;
+            } else if self.gamepads.just_disconnected() {
+                run.paused = true;
+            }
+            let stopped = run.paused || self.console_open();
+
+            self.mixer.set_ducked(run.paused);
+            let delta = scaled_frame_time(stopped, run.time_scale);
+            run.camera.update(delta);
+            // the hit-stops are disabled along with the other camera effects
+            let frozen = self.settings.screen_shake && run.camera.is_frozen();
+            advance_game_time(stopped || frozen, run.time_scale);
+
+            if !stopped {
+                self.update_attack(&mut run);
+                self.update_characters(&mut run);
+            }
+
+            let shake = self.settings.screen_shake && !stopped;
+            let (offset, zoom) = run.camera.view(shake);
+            self.screen_drawer
+                .draw_scaled_with_view(offset, zoom, || self.draw_run(&run));
+
+            if run.life_bar.is_empty() {
+                self.mixer.play(Effect::GameOver);
+                return run;
             }
 
             next_frame().await;
         }
     }
+
+    /// Handles the typing in the console and runs the command entered on the run,
+    /// returning true if the command asks for a reload.
+    #[cfg(debug_assertions)]
+    fn update_console(&mut self, run: &mut Run) -> bool {
+        let Some(line) = self.console.update() else {
+            return false;
+        };
+        let mut context = Context {
+            run,
+            animations: &self.animations,
+            reload: false,
+        };
+        self.console.execute(&line, &mut context);
+        context.reload
+    }
+
+    /// Reloads the textures and the tuning changed on disk, in the debug builds, or
+    /// every file along with the settings when `forced` by the console.
+    async fn hot_reload(&mut self, run: &mut Run, forced: bool) {
+        // polled even when forced, to record the times of the files reloaded
+        let polled = self.watcher.poll(&self.assets, &self.animations);
+        let changes = if forced { Changes::all() } else { polled };
+
+        if changes.settings {
+            self.settings = Settings::load();
+            self.screen_drawer.scaling = self.settings.scaling;
+            set_fullscreen(self.settings.fullscreen);
+            self.mixer.update(self.settings.volumes);
+        }
+        if changes.textures {
+            // an invalid file is reported and the previous textures and animations kept
+            match Textures::load(&self.assets).await {
+                Ok(textures) => self.textures = textures,
+                Err(err) => eprintln!("{err}"),
+            }
+            match Animations::load(&self.assets).await {
+                Ok(animations) => self.animations = animations,
+                Err(err) => eprintln!("{err}"),
+            }
+            run.refresh_textures(&self.textures, &self.animations);
+        }
+        if changes.tuning {
+            // an invalid file is reported and ignored, to be fixed while the game runs
+            if let Some(tuning) = Tuning::read(&self.assets) {
+                run.apply_tuning(&tuning);
+                self.tuning = tuning;
+            }
+        }
+    }
+
+    /// Buffers the attack presses and starts the attack once the cooldown is available.
+    fn update_attack(&self, run: &mut Run) {
+        let input = self.input();
+        let player = &mut run.player;
+
+        player.update_direction(input);
+        player.animate(&self.animations);
+
+        if let Some(direction) = self.pointer.strike() {
+            run.attack_buffer.press(direction);
+        } else if input.is_pressed(Action::Attack) {
+            run.attack_buffer.press(player.aimed_direction());
+        } else if let Some(direction) = input.pressed_direction() {
+            match self.settings.control_scheme {
+                ControlScheme::Classic => run.attack_buffer.replace(direction),
+                ControlScheme::DirectStrike => run.attack_buffer.press(direction),
+            }
+        }
+        // the press stays buffered while the player is hurt
+        if run.attack_cooldown.available() && player.can_attack() {
+            if let Some(direction) = run.attack_buffer.take() {
+                run.attack_cooldown.start();
+                player.start_attack(direction, &self.animations);
+                self.mixer.play(Effect::Slash);
+            }
+        }
+    }
+
+    /// Spawns and moves the enemies, and resolves their collisions.
+    fn update_characters(&self, run: &mut Run) {
+        if run.enemy_spawner.tick_and_finished() {
+            let enemy = Enemy::new_random(ENEMY_SIZE, ENEMY_SIZE, &self.animations);
+            let position = enemy.character.position().into();
+
+            run.particles.emit(&DUST, position, Vec2::zero());
+            run.enemies.push(enemy);
+        }
+
+        for enemy in &mut run.enemies {
+            enemy.move_and_animate(self.tuning.enemy_speed, &self.animations);
+
+            if !enemy.alive {
+                continue;
+            }
+            if run.player.kill(enemy) {
+                let position = enemy.character.position().into();
+                let direction = run.player.character.direction.into();
+
+                run.particles.emit(&BLOOD, position, direction);
+                run.particles.emit(&SPARKS, position, direction);
+                run.camera.shake(0.3);
+                run.camera.punch_zoom(0.05);
+                run.camera.hit_stop(0.05);
+                run.score += 10;
+                run.attack_cooldown.reset();
+                let elapsed = game_time() - run.started_at;
+                run.enemy_spawner.delay = 1.0 / elapsed.mul_add(0.1, 0.5);
+                enemy.die(&self.animations);
+                self.mixer.play(Effect::Kill);
+            }
+            if enemy.alive && enemy.character.collide(&run.player.character) {
+                enemy.die(&self.animations);
+
+                if !run.god_mode {
+                    run.life_bar.decrement();
+                    run.player.hurt(&self.animations);
+                    run.camera.shake(0.6);
+                    run.camera.hit_stop(0.1);
+                    self.mixer.play(Effect::Damage);
+                }
+            }
+        }
+
+        run.enemies.retain(|enemy| !enemy.is_gone());
+        run.particles.update();
+    }
+
+    fn draw_run(&self, run: &Run) {
+        clear_background(LIME);
+        self.textures.background.draw(0., 0.);
+        run.player.draw();
+        run.player.draw_slash();
+        run.enemies.iter().for_each(Enemy::draw);
+        run.particles.draw();
+        run.life_bar.draw();
+        self.pointer.draw_hints();
+
+        if self.settings.show_hit_boxes || self.debug.is_visible() {
+            run.player.character.draw_hit_box();
+            for enemy in &run.enemies {
+                enemy.character.draw_hit_box();
+            }
+        }
+
+        self.debug.draw(run, &self.fonts);
+
+        let score = &format!("Score: {}", run.score);
+        Fonts::draw_left(score, GAME_WIDTH - MARGIN, MARGIN, self.fonts.sized(8));
+
+        if run.paused {
+            let (x, y) = (GAME_WIDTH / 2., GAME_HEIGHT / 2.);
+            Fonts::draw_centered("Paused", x, y, self.fonts.sized(20));
+        }
+
+        #[cfg(debug_assertions)]
+        self.console.draw(&self.fonts);
+    }
 }
 
 fn window_conf() -> Conf {
@@ -180,6 +628,8 @@ fn window_conf() -> Conf {
         window_title: "Arcade Knight".to_owned(),
         window_width: (GAME_WIDTH * 3.) as i32,
         window_height: (GAME_HEIGHT * 3.) as i32,
+        fullscreen: Settings::load().fullscreen,
+        window_resizable: true,
         ..Conf::default()
     }
 }
@@ -0,0 +1,181 @@
+//! The animation manifest, describing the texture atlases and the animation clips
+//! played from them.
+
+use std::{collections::BTreeMap, rc::Rc};
+
+use macroquad::prelude::*;
+use nanoserde::DeRon;
+
+use crate::{
+    animation::{Clip, TextureAtlas},
+    assets::{scalable_texture, Assets},
+};
+
+#[derive(DeRon)]
+pub struct AnimationManifest {
+    #[nserde(default)]
+    pub atlases: BTreeMap<String, AtlasDefinition>,
+    /// Sprite sheets exported by Aseprite, relative to the asset root, by atlas name,
+    /// their tags being added to the clips.
+    #[nserde(default)]
+    pub sheets: BTreeMap<String, String>,
+    #[nserde(default)]
+    pub clips: BTreeMap<String, ClipDefinition>,
+    /// Recolored copies of the clips of a character, by skin name, the name replacing
+    /// the one of the character in the names of the clips.
+    #[nserde(default)]
+    pub skins: BTreeMap<String, SkinDefinition>,
+}
+
+/// A texture cut into a grid of tiles of the same size.
+#[derive(DeRon)]
+pub struct AtlasDefinition {
+    /// Image of the atlas, relative to the asset root.
+    pub texture: String,
+    /// Width and height of a tile, in pixels.
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+    /// Pivots of the tiles not drawn around their center, by tile index, relative to the
+    /// top left corner of the tile.
+    #[nserde(default)]
+    pub pivots: BTreeMap<usize, (f32, f32)>,
+}
+
+/// The frames of an animation, taken from one atlas.
+#[derive(DeRon)]
+pub struct ClipDefinition {
+    pub atlas: String,
+    /// Indexes of the tiles of the frames, counted row by row in the atlas.
+    pub frames: Vec<usize>,
+    /// Duration of the frames without their own duration, in seconds.
+    #[nserde(default = 0.1)]
+    pub frame_duration: f64,
+    /// Duration of each frame, overriding `frame_duration` when not empty.
+    #[nserde(default)]
+    pub durations: Vec<f64>,
+    /// Named events with the index of the frame triggering them, in the clip.
+    #[nserde(default)]
+    pub events: Vec<(usize, String)>,
+    #[nserde(default)]
+    pub looping: bool,
+    /// Whether the frames are mirrored horizontally.
+    #[nserde(default)]
+    pub flip_x: bool,
+    /// Whether the frames are mirrored vertically.
+    #[nserde(default)]
+    pub flip_y: bool,
+}
+
+/// A character drawn with other colors.
+#[derive(DeRon)]
+pub struct SkinDefinition {
+    /// Name of the character whose clips are recolored, such as `enemy`.
+    pub character: String,
+    /// Palette file swapping the colors, relative to the asset root.
+    pub palette: String,
+    /// Best score from which the skin can be chosen for the player, the skin being
+    /// available from the start when it is omitted.
+    #[nserde(default)]
+    pub unlock_score: u32,
+}
+
+impl AnimationManifest {
+    pub async fn load(assets: &Assets, file: &str) -> Result<Self, String> {
+        let content = assets.read_string(file).await?;
+
+        Self::deserialize_ron(&content)
+            .map_err(|err| format!("Invalid {}: {err}", assets.path(file)))
+    }
+}
+
+impl AtlasDefinition {
+    /// Loads the texture of the atlas, checking that the grid fits in it.
+    ///
+    /// Returns the atlas and its decoded image.
+    pub async fn load(&self, name: &str, assets: &Assets) -> Result<(TextureAtlas, Image), String> {
+        let image = assets.image(&self.texture).await?;
+        let texture = scalable_texture(&image);
+        let (w, h) = self.tile_size;
+
+        if w <= 0. || h <= 0. {
+            return Err(format!("The tiles of the atlas {name} have no size"));
+        }
+        if self.columns as f32 * w > texture.width() || self.rows as f32 * h > texture.height() {
+            return Err(format!(
+                "The grid of the atlas {name} is larger than {} ({}x{})",
+                self.texture,
+                texture.width(),
+                texture.height()
+            ));
+        }
+        let mut atlas = TextureAtlas::from_grid(texture, self.tile_size, self.columns, self.rows);
+        for (tile, (x, y)) in &self.pivots {
+            if *tile >= atlas.tile_count() {
+                return Err(format!(
+                    "The pivot of the atlas {name} is on the missing tile {tile}"
+                ));
+            }
+            atlas.set_pivot(*tile, vec2(*x, *y));
+        }
+        Ok((atlas, image))
+    }
+}
+
+impl ClipDefinition {
+    /// Creates the clip, checking its frames against the tiles of its atlas.
+    pub fn build(
+        &self,
+        name: &str,
+        atlases: &BTreeMap<String, Rc<TextureAtlas>>,
+    ) -> Result<Clip, String> {
+        let atlas = atlases
+            .get(&self.atlas)
+            .ok_or_else(|| format!("Unknown atlas {} in the clip {name}", self.atlas))?;
+
+        if self.frames.is_empty() {
+            return Err(format!("The clip {name} has no frames"));
+        }
+        if let Some(frame) = self.frames.iter().find(|tile| **tile >= atlas.tile_count()) {
+            return Err(format!(
+                "The frame {frame} of the clip {name} is out of the {} tiles of {}",
+                atlas.tile_count(),
+                self.atlas
+            ));
+        }
+
+        let durations = if self.durations.is_empty() {
+            vec![self.frame_duration; self.frames.len()]
+        } else if self.durations.len() == self.frames.len() {
+            self.durations.clone()
+        } else {
+            return Err(format!(
+                "The clip {name} has {} durations for {} frames",
+                self.durations.len(),
+                self.frames.len()
+            ));
+        };
+        if durations.iter().any(|duration| *duration <= 0.) {
+            return Err(format!("The clip {name} has frames without duration"));
+        }
+        if let Some((frame, event)) = self
+            .events
+            .iter()
+            .find(|(frame, _)| *frame >= self.frames.len())
+        {
+            return Err(format!(
+                "The event {event} of the clip {name} is on the missing frame {frame}"
+            ));
+        }
+
+        Ok(Clip::new(
+            name.to_owned(),
+            atlas.clone(),
+            self.frames.clone(),
+            durations,
+            self.events.clone(),
+            self.looping,
+        )
+        .flipped(self.flip_x, self.flip_y))
+    }
+}
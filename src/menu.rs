@@ -0,0 +1,48 @@
+use crate::{
+    input::{Action, Input},
+    resources::Fonts,
+};
+
+/// A vertical list of items navigated with the up and down actions.
+pub struct Menu {
+    selected: usize,
+    len: usize,
+}
+
+impl Menu {
+    /// Creates a menu of `len` items with the first one selected.
+    pub const fn new(len: usize) -> Self {
+        Self { selected: 0, len }
+    }
+
+    /// Returns the index of the selected item.
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Moves the selection following the pressed actions, wrapping around the ends.
+    pub fn navigate(&mut self, input: Input) {
+        if input.is_pressed(Action::Up) {
+            self.selected = (self.selected + self.len - 1) % self.len;
+        }
+        if input.is_pressed(Action::Down) {
+            self.selected = (self.selected + 1) % self.len;
+        }
+    }
+
+    /// Draws the items centered around `x` starting from `y`, marking the selected one.
+    pub fn draw(&self, items: &[String], x: f32, y: f32, fonts: &Fonts) {
+        let spacing = 12.;
+
+        for (index, item) in items.iter().enumerate() {
+            let text = if index == self.selected {
+                format!("> {item} <")
+            } else {
+                item.clone()
+            };
+            let y = (index as f32).mul_add(spacing, y);
+
+            Fonts::draw_centered(&text, x, y, fonts.sized(8));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! The mixer of the sounds, applying the volumes chosen by the player.
+
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+use nanoserde::{DeRon, SerRon};
+
+use crate::{
+    music::Music,
+    resources::{Effect, Sounds, Track},
+};
+
+/// Change of a volume for each press in the options.
+pub const VOLUME_STEP: f32 = 0.1;
+/// Factor applied to the music volume while the game is paused.
+const DUCKED_VOLUME: f32 = 0.3;
+/// Maximum number of instances of the same effect playing at once.
+const MAX_EFFECT_INSTANCES: usize = 2;
+
+/// The volumes of the sound channels, between 0 and 1.
+#[derive(Clone, Copy, SerRon, DeRon)]
+pub struct Volumes {
+    #[nserde(default = 1.0)]
+    pub master: f32,
+    #[nserde(default = 0.6)]
+    pub music: f32,
+    #[nserde(default = 0.8)]
+    pub effects: f32,
+    #[nserde(default)]
+    pub muted: bool,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 0.6,
+            effects: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl Volumes {
+    /// Returns the volume of the music channel, after the master volume and the mute.
+    fn music_gain(self) -> f32 {
+        if self.muted {
+            0.
+        } else {
+            self.master * self.music
+        }
+    }
+
+    /// Returns the volume of the effects channel, after the master volume and the mute.
+    fn effects_gain(self) -> f32 {
+        if self.muted {
+            0.
+        } else {
+            self.master * self.effects
+        }
+    }
+}
+
+/// Changes the volume by the given number of steps, keeping it between 0 and 1.
+pub fn step_volume(volume: f32, steps: f32) -> f32 {
+    // counted in whole steps so that repeated changes do not accumulate rounding errors
+    let steps = (volume / VOLUME_STEP).round() + steps;
+    (steps * VOLUME_STEP).clamp(0., 1.)
+}
+
+/// Plays the sound effects and the music through the volume channels.
+pub struct Mixer {
+    sounds: Sounds,
+    music: Music,
+    volumes: Volumes,
+    /// Whether the music is lowered.
+    ducked: bool,
+    /// Times at which the playing instances of each effect end, in the order of `Effect::iter`.
+    instances: RefCell<Vec<Vec<f64>>>,
+}
+
+impl Mixer {
+    pub fn new(sounds: Sounds, volumes: Volumes) -> Self {
+        Self {
+            sounds,
+            music: Music::new(),
+            volumes,
+            ducked: false,
+            instances: RefCell::new(Effect::iter().map(|_| Vec::new()).collect()),
+        }
+    }
+
+    /// Applies the volumes and fades the music, to be called once per frame.
+    pub fn update(&mut self, volumes: Volumes) {
+        self.volumes = volumes;
+
+        let ducking = if self.ducked { DUCKED_VOLUME } else { 1. };
+        self.music
+            .update(&self.sounds, self.volumes.music_gain() * ducking);
+    }
+
+    /// Plays the sound effect, unless too many instances of it are already playing.
+    pub fn play(&self, effect: Effect) {
+        let now = get_time();
+        let mut instances = self.instances.borrow_mut();
+        let instances = &mut instances[effect as usize];
+        instances.retain(|end| *end > now);
+
+        if instances.len() < MAX_EFFECT_INSTANCES {
+            instances.push(now + self.sounds.duration(effect));
+            self.sounds.play(effect, self.volumes.effects_gain());
+        }
+    }
+
+    /// Crossfades to the music track, which is no longer ducked.
+    pub fn play_music(&mut self, track: Track) {
+        self.ducked = false;
+        self.music.play(track, &self.sounds);
+    }
+
+    /// Lowers the music or restores its volume.
+    pub const fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+}
@@ -0,0 +1,59 @@
+//! The music of the game, crossfading between the tracks of the screens.
+
+use macroquad::prelude::*;
+use quad_snd::Playback;
+
+use crate::resources::{Sounds, Track};
+
+/// Duration of the crossfade between two tracks.
+const CROSSFADE_SECONDS: f32 = 1.;
+
+pub struct Music {
+    /// The track being played, with its playback and volume.
+    current: Option<(Track, Playback, f32)>,
+    /// The previous tracks fading out, with their volume.
+    fading: Vec<(Playback, f32)>,
+}
+
+impl Music {
+    pub const fn new() -> Self {
+        Self {
+            current: None,
+            fading: Vec::new(),
+        }
+    }
+
+    /// Crossfades to the given track, unless it is already playing.
+    pub fn play(&mut self, track: Track, sounds: &Sounds) {
+        if matches!(self.current, Some((current, ..)) if current == track) {
+            return;
+        }
+        if let Some((_, playback, volume)) = self.current.take() {
+            self.fading.push((playback, volume));
+        }
+        self.current = Some((track, sounds.play_looped(track, 0.), 0.));
+    }
+
+    /// Fades the tracks in and out, playing them at the given volume, to be called once per frame.
+    pub fn update(&mut self, sounds: &Sounds, gain: f32) {
+        let step = get_frame_time() / CROSSFADE_SECONDS;
+
+        if let Some((_, playback, volume)) = &mut self.current {
+            *volume = (*volume + step).min(1.);
+            sounds.set_volume(playback, *volume * gain);
+        }
+
+        let (faded, fading) = std::mem::take(&mut self.fading)
+            .into_iter()
+            .map(|(playback, volume)| (playback, volume - step))
+            .partition(|(_, volume)| *volume <= 0.);
+
+        self.fading = fading;
+        for (playback, volume) in &self.fading {
+            sounds.set_volume(playback, *volume * gain);
+        }
+        for (playback, _) in faded {
+            sounds.stop(playback);
+        }
+    }
+}
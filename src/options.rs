@@ -0,0 +1,91 @@
+//! The items of the options screen and how they change the settings.
+
+use crate::{mixer::step_volume, settings::Settings};
+
+/// An item of the options screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionItem {
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    Mute,
+    Fullscreen,
+    Scaling,
+    ScreenShake,
+    HitBoxes,
+    Skin,
+    Controls,
+    Back,
+}
+
+#[allow(clippy::enum_glob_use)]
+use OptionItem::*;
+
+impl OptionItem {
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [
+            MasterVolume,
+            MusicVolume,
+            EffectsVolume,
+            Mute,
+            Fullscreen,
+            Scaling,
+            ScreenShake,
+            HitBoxes,
+            Skin,
+            Controls,
+            Back,
+        ]
+        .iter()
+        .copied()
+    }
+
+    /// Returns the text of the item, showing its current value.
+    ///
+    /// The skin shown is the one the player is drawn with, which is the default one when
+    /// the chosen skin is not unlocked.
+    pub fn label(self, settings: &Settings, player_skin: &str) -> String {
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.);
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+        let volumes = &settings.volumes;
+
+        match self {
+            MasterVolume => format!("Master volume: {}", percent(volumes.master)),
+            MusicVolume => format!("Music volume: {}", percent(volumes.music)),
+            EffectsVolume => format!("Effects volume: {}", percent(volumes.effects)),
+            Mute => format!("Mute: {}", on_off(volumes.muted)),
+            Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            Scaling => format!("Scaling: {}", settings.scaling.name()),
+            ScreenShake => format!("Screen shake: {}", on_off(settings.screen_shake)),
+            HitBoxes => format!("Hit boxes: {}", on_off(settings.show_hit_boxes)),
+            // the skins of the player are named `player_<name>`
+            Skin => format!(
+                "Skin: {}",
+                player_skin.strip_prefix("player_").unwrap_or("default")
+            ),
+            Controls => "Controls".to_owned(),
+            Back => "Back".to_owned(),
+        }
+    }
+
+    /// Changes the volume by the given number of steps, or toggles the option.
+    ///
+    /// The scaling mode cycles backward for negative steps and forward otherwise. The
+    /// skin is changed by the game, which knows the unlocked skins.
+    pub fn change(self, settings: &mut Settings, steps: f32) {
+        let volumes = &mut settings.volumes;
+
+        match self {
+            MasterVolume => volumes.master = step_volume(volumes.master, steps),
+            MusicVolume => volumes.music = step_volume(volumes.music, steps),
+            EffectsVolume => volumes.effects = step_volume(volumes.effects, steps),
+            Mute => volumes.muted = !volumes.muted,
+            Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Scaling if steps < 0. => settings.scaling = settings.scaling.previous(),
+            Scaling => settings.scaling = settings.scaling.next(),
+            ScreenShake => settings.screen_shake = !settings.screen_shake,
+            HitBoxes => settings.show_hit_boxes = !settings.show_hit_boxes,
+            Skin | Controls | Back => {}
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! A packer combining loose images into one texture when the game starts, so that they
+//! are drawn from a single atlas.
+
+use std::{cmp::Reverse, convert::TryFrom};
+
+use macroquad::prelude::*;
+
+/// Transparent pixels left between the images, so that a scaled image never shows the
+/// edge of its neighbour.
+const PADDING: usize = 1;
+
+/// Packs the images into one, in rows filled from the tallest image to the shortest.
+///
+/// Returns the packed image and the rectangle of each image in it, in the order of the
+/// given images.
+pub fn pack(images: &[Image]) -> (Image, Vec<Rect>) {
+    let area: usize = images
+        .iter()
+        .map(|image| (image.width() + PADDING) * (image.height() + PADDING))
+        .sum();
+    let widest = images.iter().map(Image::width).max().unwrap_or(1);
+
+    // a square power of two when the images fill it, wider for a very wide image
+    let mut width = widest.next_power_of_two();
+    while width * width < area {
+        width *= 2;
+    }
+
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by_key(|index| Reverse(images[*index].height()));
+
+    let mut positions = vec![(0, 0); images.len()];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for index in order {
+        let image = &images[index];
+
+        if x + image.width() > width {
+            x = 0;
+            y += row_height + PADDING;
+            row_height = 0;
+        }
+        positions[index] = (x, y);
+        x += image.width() + PADDING;
+        row_height = row_height.max(image.height());
+    }
+    let height = y + row_height;
+
+    let mut packed = Image::gen_image_color(
+        u16::try_from(width).expect("Packed image too wide"),
+        u16::try_from(height).expect("Packed image too tall"),
+        BLANK,
+    );
+    let pixels = packed.get_image_data_mut();
+    for (image, (x, y)) in images.iter().zip(&positions) {
+        let source = image.get_image_data();
+
+        for row in 0..image.height() {
+            let line = &source[row * image.width()..][..image.width()];
+            pixels[(y + row) * width + x..][..image.width()].copy_from_slice(line);
+        }
+    }
+
+    let rects = images
+        .iter()
+        .zip(positions)
+        .map(|(image, (x, y))| {
+            Rect::new(
+                x as f32,
+                y as f32,
+                image.width() as f32,
+                image.height() as f32,
+            )
+        })
+        .collect();
+
+    (packed, rects)
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_sign_loss)] // positions of the rects in the packed image
+mod tests {
+    use super::*;
+
+    fn image(width: u16, height: u16, color: Color) -> Image {
+        Image::gen_image_color(width, height, color)
+    }
+
+    fn images() -> Vec<Image> {
+        vec![
+            image(10, 4, RED),
+            image(30, 20, GREEN),
+            image(7, 7, BLUE),
+            image(16, 16, WHITE),
+            image(3, 12, YELLOW),
+        ]
+    }
+
+    fn pixel(image: &Image, x: usize, y: usize) -> [u8; 4] {
+        image.get_image_data()[y * image.width() + x]
+    }
+
+    /// Returns true if the rectangles are apart by at least the padding.
+    fn padded_apart(a: &Rect, b: &Rect) -> bool {
+        let padding = PADDING as f32;
+
+        a.right() + padding <= b.left()
+            || b.right() + padding <= a.left()
+            || a.bottom() + padding <= b.top()
+            || b.bottom() + padding <= a.top()
+    }
+
+    #[test]
+    fn rects_are_apart_by_the_padding() {
+        let (_, rects) = pack(&images());
+
+        for (index, a) in rects.iter().enumerate() {
+            for b in &rects[index + 1..] {
+                assert!(padded_apart(a, b), "{:?} is too close to {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn rects_keep_the_size_and_order_of_the_images() {
+        let images = images();
+        let (packed, rects) = pack(&images);
+
+        for (image, rect) in images.iter().zip(&rects) {
+            assert_eq!(
+                rect.size(),
+                vec2(image.width() as f32, image.height() as f32)
+            );
+            assert!(rect.right() <= packed.width() as f32);
+            assert!(rect.bottom() <= packed.height() as f32);
+        }
+    }
+
+    #[test]
+    fn pixels_are_copied_and_padding_stays_transparent() {
+        let images = images();
+        let (packed, rects) = pack(&images);
+        let blank = [0; 4];
+
+        for (image, rect) in images.iter().zip(&rects) {
+            let (x, y) = (rect.x as usize, rect.y as usize);
+
+            for row in 0..image.height() {
+                for column in 0..image.width() {
+                    assert_eq!(
+                        pixel(&packed, x + column, y + row),
+                        pixel(image, column, row)
+                    );
+                }
+            }
+            if x + image.width() < packed.width() {
+                assert_eq!(pixel(&packed, x + image.width(), y), blank);
+            }
+            if y + image.height() < packed.height() {
+                assert_eq!(pixel(&packed, x, y + image.height()), blank);
+            }
+        }
+    }
+
+    #[test]
+    fn no_images_give_an_empty_atlas() {
+        let (packed, rects) = pack(&[]);
+
+        assert!(rects.is_empty());
+        assert_eq!(packed.height(), 0);
+    }
+
+    #[test]
+    fn a_very_wide_image_widens_the_atlas() {
+        let images = [image(300, 2, RED), image(8, 8, BLUE), image(8, 8, GREEN)];
+        let (packed, rects) = pack(&images);
+
+        // a single row, next to the shorter images sorted first
+        assert_eq!((packed.width(), packed.height()), (512, 8));
+        assert!(padded_apart(&rects[1], &rects[2]));
+        assert!(rects.iter().all(|rect| rect.right() <= 512.));
+        assert_eq!(
+            pixel(&packed, 299, rects[0].y as usize),
+            pixel(&images[0], 299, 0)
+        );
+    }
+}
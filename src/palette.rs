@@ -0,0 +1,58 @@
+//! Palette swaps, recoloring the atlases of a character to create its skins without
+//! new sprite sheets.
+
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+use nanoserde::DeRon;
+
+use crate::assets::Assets;
+
+/// A palette file, relative to the asset root.
+#[derive(DeRon)]
+struct PaletteFile {
+    /// Replacement of each color, both written `#rrggbb`.
+    colors: BTreeMap<String, String>,
+}
+
+/// Colors replaced by a skin.
+pub struct Palette {
+    swaps: Vec<([u8; 3], [u8; 3])>,
+}
+
+impl Palette {
+    pub async fn load(assets: &Assets, file: &str) -> Result<Self, String> {
+        let path = assets.path(file);
+        let content = assets.read_string(file).await?;
+        let palette = PaletteFile::deserialize_ron(&content)
+            .map_err(|err| format!("Invalid palette {path}: {err}"))?;
+
+        let color =
+            |text: &str| parse_color(text).ok_or_else(|| format!("Invalid color {text} in {path}"));
+        let swaps = palette
+            .colors
+            .iter()
+            .map(|(from, to)| Ok((color(from)?, color(to)?)))
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { swaps })
+    }
+
+    /// Replaces the colors of the image, keeping the transparency of the pixels.
+    pub fn apply(&self, image: &mut Image) {
+        for pixel in image.get_image_data_mut() {
+            let swap = self.swaps.iter().find(|(from, _)| pixel[..3] == from[..]);
+
+            if let Some((_, to)) = swap {
+                pixel[..3].copy_from_slice(to);
+            }
+        }
+    }
+}
+
+fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |start: usize| u8::from_str_radix(hex.get(start..start + 2)?, 16).ok();
+
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
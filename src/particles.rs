@@ -0,0 +1,176 @@
+//! Small particles emitted for the visual feedback of the game.
+
+use std::f32::consts::TAU;
+
+use macroquad::{prelude::*, rand::gen_range};
+
+use crate::timers::game_frame_time;
+
+/// Maximum number of particles alive at once, new ones being dropped beyond it.
+const MAX_PARTICLES: usize = 512;
+
+/// Describes the particles emitted at once by an effect.
+pub struct Emitter {
+    /// Number of particles emitted.
+    count: usize,
+    /// Range of the initial speeds, in pixels per second.
+    speed: (f32, f32),
+    /// Angle, in radians, over which the particles spread around the emission direction.
+    spread: f32,
+    /// Range of the lifetimes, in seconds.
+    lifetime: (f32, f32),
+    /// Downward acceleration, in pixels per second squared.
+    gravity: f32,
+    /// Part of the velocity lost each second.
+    drag: f32,
+    /// Width and height of a particle.
+    size: f32,
+    /// Colors taken by the particles from their birth to their death, evenly spaced.
+    ramp: &'static [Color],
+}
+
+/// Blood splashed in the direction of the killing attack.
+pub const BLOOD: Emitter = Emitter {
+    count: 14,
+    speed: (40., 90.),
+    spread: 1.2,
+    lifetime: (0.3, 0.6),
+    gravity: 200.,
+    drag: 2.,
+    size: 2.,
+    ramp: &[
+        Color::new(0.90, 0.16, 0.22, 1.),
+        Color::new(0.55, 0.05, 0.10, 1.),
+        Color::new(0.35, 0.02, 0.05, 0.),
+    ],
+};
+
+/// Sparks flying from the blade when it hits.
+pub const SPARKS: Emitter = Emitter {
+    count: 8,
+    speed: (80., 140.),
+    spread: 0.8,
+    lifetime: (0.1, 0.25),
+    gravity: 0.,
+    drag: 4.,
+    size: 1.,
+    ramp: &[WHITE, YELLOW, Color::new(1., 0.63, 0., 0.)],
+};
+
+/// Dust raised where an enemy appears.
+pub const DUST: Emitter = Emitter {
+    count: 10,
+    speed: (10., 30.),
+    spread: TAU,
+    lifetime: (0.3, 0.6),
+    gravity: -10.,
+    drag: 3.,
+    size: 2.,
+    ramp: &[
+        Color::new(0.83, 0.76, 0.62, 0.8),
+        Color::new(0.83, 0.76, 0.62, 0.),
+    ],
+};
+
+#[derive(Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    /// Time elapsed since the emission of the particle.
+    age: f32,
+    lifetime: f32,
+    gravity: f32,
+    drag: f32,
+    size: f32,
+    ramp: &'static [Color],
+}
+
+impl Particle {
+    fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    /// Returns the color of the ramp at the current age of the particle.
+    fn color(&self) -> Color {
+        let last = self.ramp.len() - 1;
+        let progress = (self.age / self.lifetime).min(1.) * last as f32;
+
+        #[allow(clippy::cast_sign_loss)]
+        let index = (progress.floor() as usize).min(last);
+        let (from, to) = (self.ramp[index], self.ramp[(index + 1).min(last)]);
+        let t = progress - index as f32;
+
+        Color::new(
+            (to.r - from.r).mul_add(t, from.r),
+            (to.g - from.g).mul_add(t, from.g),
+            (to.b - from.b).mul_add(t, from.b),
+            (to.a - from.a).mul_add(t, from.a),
+        )
+    }
+}
+
+/// The particles of the game, stored in a pool whose dead slots are reused.
+#[derive(Default)]
+pub struct Particles {
+    pool: Vec<Particle>,
+}
+
+impl Particles {
+    /// Emits the particles of the emitter from the position, spreading around the direction.
+    pub fn emit(&mut self, emitter: &Emitter, position: Vec2, direction: Vec2) {
+        let angle = direction.y.atan2(direction.x);
+
+        for _ in 0..emitter.count {
+            let angle = angle + gen_range(-emitter.spread / 2., emitter.spread / 2.);
+            let speed = gen_range(emitter.speed.0, emitter.speed.1);
+
+            self.spawn(Particle {
+                position,
+                velocity: vec2(angle.cos(), angle.sin()) * speed,
+                age: 0.,
+                lifetime: gen_range(emitter.lifetime.0, emitter.lifetime.1),
+                gravity: emitter.gravity,
+                drag: emitter.drag,
+                size: emitter.size,
+                ramp: emitter.ramp,
+            });
+        }
+    }
+
+    fn spawn(&mut self, particle: Particle) {
+        if let Some(slot) = self.pool.iter_mut().find(|slot| !slot.is_alive()) {
+            *slot = particle;
+        } else if self.pool.len() < MAX_PARTICLES {
+            self.pool.push(particle);
+        }
+    }
+
+    /// Moves and ages the particles following the game time.
+    pub fn update(&mut self) {
+        let delta = game_frame_time();
+
+        for particle in self.pool.iter_mut().filter(|particle| particle.is_alive()) {
+            particle.velocity.y += particle.gravity * delta;
+            particle.velocity *= particle.drag.mul_add(-delta, 1.).max(0.);
+            particle.position += particle.velocity * delta;
+            particle.age += delta;
+        }
+    }
+
+    /// Returns the number of particles alive.
+    pub fn count(&self) -> usize {
+        self.pool
+            .iter()
+            .filter(|particle| particle.is_alive())
+            .count()
+    }
+
+    pub fn draw(&self) {
+        for particle in self.pool.iter().filter(|particle| particle.is_alive()) {
+            let Particle { position, size, .. } = *particle;
+            let (x, y) = (position.x - size / 2., position.y - size / 2.);
+
+            draw_rectangle(x, y, size, size, particle.color());
+        }
+    }
+}
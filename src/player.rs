@@ -1,60 +1,149 @@
-use std::{ops::Range, rc::Rc};
+use std::f32::consts::PI;
 
 use macroquad::prelude::*;
 
 use crate::{
-    animation::{Animation, TextureAtlas},
+    animation::DrawOptions,
+    animator::{AnimationState, Animator},
     character::Character,
     direction::Direction,
     enemy::Enemy,
-    resources::{Animations, Textures},
+    input::{Action, Input},
+    resources::Animations,
+    timers::Cooldown,
     GAME_HEIGHT, GAME_WIDTH,
 };
 
+/// Angle, in radians, swept by the sword around the facing direction.
+const SLASH_ARC: f32 = PI * 0.8;
+/// Length of the trail, as a part of the arc.
+const SLASH_TRAIL: f32 = 0.6;
+/// Distance from the player to the edge of the trail.
+const SLASH_OUTER_RADIUS: f32 = 24.;
+/// Distance from the player to the inner side of the trail, where it is the widest.
+const SLASH_INNER_RADIUS: f32 = 12.;
+/// Number of segments drawn to approximate the arc.
+const SLASH_SEGMENTS: usize = 8;
+/// Duration of the flash of the player when hit.
+const HURT_FLASH: f64 = 0.3;
+/// Color of the player during the flash.
+const HURT_TINT: Color = Color::new(1., 0.3, 0.3, 1.);
+/// Event of the attack clips starting the frames in which the sword kills.
+const HIT_START: &str = "hit_start";
+/// Event of the attack clips ending the frames in which the sword kills.
+const HIT_END: &str = "hit_end";
+/// Event of the attack clips starting the frames in which the sword swings, drawing its
+/// trail.
+const SWING_START: &str = "swing_start";
+/// Event of the attack clips ending the swing, which otherwise lasts until the end of
+/// the clip.
+const SWING_END: &str = "swing_end";
+
 pub struct Player {
     pub character: Character,
-    attacking: Option<AttackAnimation>,
-    player_atlas: Rc<TextureAtlas>,
+    /// Directions being held, from the least to the most recently pressed.
+    held_directions: Vec<Direction>,
+    animator: Animator,
+    /// Whether the sword kills, between the hit start and end events of the attack.
+    hitting: bool,
+    /// Unavailable while the player flashes after being hit.
+    hurt_flash: Cooldown,
 }
 
 impl Player {
-    /// Creates the player entity centered in the middle of the screen.
-    pub fn new(w: f32, h: f32, textures: &Textures) -> Self {
+    /// Creates the player entity centered in the middle of the screen, drawn with the
+    /// clips of the given skin.
+    pub fn new(w: f32, h: f32, skin: &str, animations: &Animations) -> Self {
         Self {
             character: Character::new(GAME_WIDTH / 2., GAME_HEIGHT / 2., w, h, Direction::Down),
-            attacking: None,
-            player_atlas: textures.player_atlas.clone(),
+            held_directions: Vec::new(),
+            animator: Animator::new(skin, Direction::Down, false, animations),
+            hitting: false,
+            hurt_flash: Cooldown::from_seconds(HURT_FLASH),
         }
     }
 
-    /// Updates the player's direction depending on the pressed keys.
-    pub fn update_direction(&mut self) {
-        if let Some(direction) = Direction::iter().find(|dir| is_key_down(KeyCode::from(*dir))) {
-            self.character.direction = direction
+    /// Faces the most recently pressed direction that is still held, once the current
+    /// attack is over.
+    pub fn update_direction(&mut self, input: Input) {
+        self.held_directions
+            .retain(|direction| input.is_down(Action::from(*direction)));
+
+        // Pushed from the lowest priority, so that the highest one ends up last
+        let pressed: Vec<_> = input.pressed_directions().collect();
+        for direction in pressed.into_iter().rev() {
+            self.held_directions.retain(|held| *held != direction);
+            self.held_directions.push(direction);
+        }
+
+        if self.animator.state() == AnimationState::Attack {
+            return; // the sword keeps swinging where the attack started
+        }
+        if let Some(direction) = self.held_directions.last() {
+            self.character.direction = *direction;
         }
     }
 
-    /// Starts the animation of an attack to the current direction.
-    pub fn start_attack(&mut self, animations: &Animations) {
-        self.attacking = Some(animations.player_attack(self.character.direction));
+    /// Returns the most recently pressed direction that is still held, or the facing
+    /// direction, which stays the one of the attack while it plays.
+    pub fn aimed_direction(&self) -> Direction {
+        self.held_directions
+            .last()
+            .copied()
+            .unwrap_or(self.character.direction)
     }
 
-    /// Updates the animation of the attack.
-    pub fn animate_attack(&mut self) {
-        if let Some(animation) = &mut self.attacking {
-            if animation.0.tick().is_finished() {
-                self.attacking = None;
+    /// Returns true if an attack can start, the player not being hurt.
+    pub fn can_attack(&self) -> bool {
+        self.animator.can_play(AnimationState::Attack)
+    }
+
+    /// Turns the player to the given direction and starts the animation of an attack.
+    pub fn start_attack(&mut self, direction: Direction, animations: &Animations) {
+        self.character.direction = direction;
+        self.animator.set_direction(direction, animations);
+        self.animator.play(AnimationState::Attack, animations);
+        self.handle_events();
+    }
+
+    /// Plays the animation of the player being hit, and makes them flash.
+    pub fn hurt(&mut self, animations: &Animations) {
+        self.animator.play(AnimationState::Hurt, animations);
+        self.hurt_flash.start();
+    }
+
+    /// Updates the animation, facing the current direction.
+    pub fn animate(&mut self, animations: &Animations) {
+        let direction = self.character.direction;
+        self.animator.set_direction(direction, animations);
+        self.animator.update(animations);
+        self.handle_events();
+    }
+
+    fn handle_events(&mut self) {
+        if self.animator.state() != AnimationState::Attack {
+            self.hitting = false;
+        }
+        for event in self.animator.events() {
+            match event {
+                HIT_START => self.hitting = true,
+                HIT_END => self.hitting = false,
+                _ => {}
             }
         }
     }
 
+    /// Returns the state and the frame of the animation.
+    pub const fn animation_frame(&self) -> (AnimationState, usize) {
+        (
+            self.animator.state(),
+            self.animator.animation().current_frame,
+        )
+    }
+
     /// Returns true if the player kills the given enemy with its attack.   
     pub fn kill(&self, enemy: &Enemy) -> bool {
-        self.attacking
-            .as_ref()
-            .filter(|attack| attack.is_attack_frame())
-            .map(|_| self.slash_attack())
-            .map_or(false, |slash| slash.overlaps(&enemy.character.body))
+        self.hitting && self.slash_attack().overlaps(&enemy.character.body)
     }
 
     pub fn slash_attack(&self) -> Rect {
@@ -69,15 +158,11 @@ impl Player {
             Direction::Right | Direction::Left => slash.scale(1.5, 1.),
         }
 
-
-
         // let direction = match direction {
         //     Direction::Up | Direction::Left => Vec2::from(direction) * 1.5,
         //     Direction::Down | Direction::Right => Vec2::from(direction),
         // }
 
-
-
         let mut direction = Vec2::from(direction);
 
         if direction.x < 0. || direction.y < 0. {
@@ -86,36 +171,79 @@ impl Player {
         slash.offset(direction * body.size())
     }
 
-    /// Draws the player either as idle of in an animation.
-    pub fn draw(&self) {
-        let (x, y) = self.character.position();
-
-        if let Some(animation) = &self.attacking {
-            animation.0.draw_current_centered(x, y);
-        } else {
-            self.draw_idle(x, y);
+    /// Draws the trail of the sword during the frames in which it swings.
+    pub fn draw_slash(&self) {
+        let Some(progress) = self.swing_progress() else {
+            return;
+        };
+        let center = Vec2::from(self.character.position());
+        let facing = Vec2::from(self.character.direction);
+        let start = facing.y.atan2(facing.x) - SLASH_ARC / 2.;
+
+        // The trail follows the tip of the sword, fading out and thinning behind it
+        let head = SLASH_ARC.mul_add(progress, start);
+        let tail = SLASH_ARC.mul_add(-SLASH_TRAIL, head).max(start);
+
+        // Outer and inner points along the trail, from its tail to its head
+        let points: Vec<_> = (0..=SLASH_SEGMENTS)
+            .map(|index| {
+                let t = index as f32 / SLASH_SEGMENTS as f32;
+                let angle = (head - tail).mul_add(t, tail);
+                let width = (SLASH_OUTER_RADIUS - SLASH_INNER_RADIUS) * t;
+                let direction = vec2(angle.cos(), angle.sin());
+
+                (
+                    center + direction * SLASH_OUTER_RADIUS,
+                    center + direction * (SLASH_OUTER_RADIUS - width),
+                    t,
+                )
+            })
+            .collect();
+
+        for pair in points.windows(2) {
+            let ((outer_from, inner_from, _), (outer_to, inner_to, t)) = (pair[0], pair[1]);
+            let color = Color::new(1., 1., 1., t * 0.9);
+
+            draw_triangle(outer_from, outer_to, inner_to, color);
+            draw_triangle(outer_from, inner_to, inner_from, color);
         }
     }
 
-    fn draw_idle(&self, x: f32, y: f32) {
-        let tile_index = match self.character.direction {
-            Direction::Up => 0,
-            Direction::Right => 1,
-            Direction::Down => 2,
-            Direction::Left => 3,
-        };
-        self.player_atlas.draw_tile_centered(tile_index, x, y);
+    /// Returns how far the sword has swung, between 0 and 1, during the swinging frames
+    /// of the attack clip.
+    fn swing_progress(&self) -> Option<f32> {
+        if self.animator.state() != AnimationState::Attack {
+            return None;
+        }
+        let animation = self.animator.animation();
+        let start = animation.event_frame(SWING_START)?;
+        let end = animation
+            .event_frame(SWING_END)
+            .unwrap_or_else(|| animation.frame_count());
+        let frame = animation.current_frame;
+
+        (start..end).contains(&frame).then(|| {
+            let swung = (frame - start) as f32 + animation.frame_progress();
+            swung / (end - start) as f32
+        })
     }
-}
 
-pub struct AttackAnimation(Animation);
-
-impl AttackAnimation {
-    pub fn new(player_atlas: Rc<TextureAtlas>, indexes: Range<usize>) -> Self {
-        Self(Animation::new(player_atlas, indexes.collect(), 0.05, false))
+    /// Uses the reloaded animations.
+    pub fn refresh_animations(&mut self, animations: &Animations) {
+        self.animator.reload(animations);
     }
 
-    pub const fn is_attack_frame(&self) -> bool {
-        self.0.current_frame == 2 // The third frame is when the sword is the farthest
+    pub fn draw(&self) {
+        let (x, y) = self.character.position();
+        let tint = if self.hurt_flash.available() {
+            WHITE
+        } else {
+            HURT_TINT
+        };
+        let options = DrawOptions {
+            tint,
+            ..DrawOptions::default()
+        };
+        self.animator.draw(x, y, options);
     }
 }
@@ -0,0 +1,88 @@
+//! Mouse and touch input, where a tap or a swipe strikes in a direction.
+
+use macroquad::prelude::*;
+
+use crate::{direction::Direction, screen_drawer::ScreenDrawer, GAME_HEIGHT, GAME_WIDTH};
+
+/// Minimum distance, in game pixels, for a press to be considered as a swipe.
+const SWIPE_DISTANCE: f32 = 20.;
+
+/// A release of the pointer.
+#[derive(Clone, Copy)]
+pub enum Gesture {
+    /// The pointer was released near where it was pressed, at the given game position.
+    Tap(Vec2),
+    /// The pointer was moved in the given direction while pressed.
+    Swipe(Direction),
+}
+
+/// Tracks the mouse, which also receives the touches of the screen.
+#[derive(Default)]
+pub struct Pointer {
+    /// Game position where the pointer has been pressed.
+    pressed_at: Option<Vec2>,
+    /// Gesture made during the frame.
+    gesture: Option<Gesture>,
+    /// Whether the pointer has already been used to play.
+    used: bool,
+}
+
+impl Pointer {
+    /// Updates the gesture of the frame, to be called once per frame.
+    pub fn update(&mut self, screen_drawer: &ScreenDrawer) {
+        let position = screen_drawer.to_game(mouse_position().into());
+        self.gesture = None;
+
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.pressed_at = Some(position);
+            self.used = true;
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            if let Some(pressed_at) = self.pressed_at.take() {
+                let swipe = position - pressed_at;
+
+                self.gesture = if swipe.length() < SWIPE_DISTANCE {
+                    Some(Gesture::Tap(position))
+                } else {
+                    Direction::from_vector(swipe).map(Gesture::Swipe)
+                };
+            }
+        }
+    }
+
+    /// Returns the gesture made during the frame.
+    pub const fn gesture(&self) -> Option<Gesture> {
+        self.gesture
+    }
+
+    /// Returns the direction to strike, toward the tapped quadrant of the screen or the swipe.
+    pub fn strike(&self) -> Option<Direction> {
+        match self.gesture? {
+            Gesture::Tap(position) => {
+                Direction::from_vector(position - vec2(GAME_WIDTH, GAME_HEIGHT) / 2.)
+            }
+            Gesture::Swipe(direction) => Some(direction),
+        }
+    }
+
+    /// Draws the quadrants of the screen and their directions, once the pointer has been used.
+    pub fn draw_hints(&self) {
+        if !self.used {
+            return;
+        }
+        let color = Color::new(1., 1., 1., 0.15);
+        let (w, h) = (GAME_WIDTH, GAME_HEIGHT);
+
+        draw_line(0., 0., w, h, 1., color);
+        draw_line(w, 0., 0., h, 1., color);
+
+        for direction in Direction::iter() {
+            let forward = Vec2::from(direction);
+            let side = vec2(-forward.y, forward.x);
+            let tip = vec2(w, h) / 2. + forward * (w.min(h) / 2. - 8.);
+
+            let base = tip - forward * 6.;
+            draw_triangle(tip, base + side * 5., base - side * 5., color);
+        }
+    }
+}
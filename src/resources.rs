@@ -1,84 +1,284 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, iter, rc::Rc};
 
 use macroquad::prelude::*;
+use quad_snd::{AudioContext, PlaySoundParams, Playback, Sound};
 
 use crate::{
-    animation::{Animation, TextureAtlas},
+    animation::{Clip, DrawOptions, TextureAtlas, Tile},
+    aseprite,
+    assets::{scalable_texture, Assets},
     direction::Direction,
-    player::AttackAnimation,
-    screen_drawer::load_scalable_texture,
+    manifest::AnimationManifest,
+    packer,
+    palette::Palette,
 };
 
+/// The images outside of the animations, drawn from one packed atlas.
 pub struct Textures {
-    pub player_atlas: Rc<TextureAtlas>,
-    pub enemy_atlas: Rc<TextureAtlas>,
-    pub heart: Texture2D,
-    pub empty_heart: Texture2D,
-    pub background: Texture2D,
+    pub heart: Sprite,
+    pub empty_heart: Sprite,
+    pub background: Sprite,
 }
 
 impl Textures {
-    pub async fn load() -> Self {
-        let player_texture = load_scalable_texture("resources/player_sprite.png").await;
-        let enemy_texture = load_scalable_texture("resources/enemy_sprite.png").await;
-
-        Self {
-            player_atlas: Rc::new(TextureAtlas::from_grid(player_texture, (50., 50.), 4, 5)),
-            enemy_atlas: Rc::new(TextureAtlas::from_grid(enemy_texture, (24., 24.), 3, 4)),
-            heart: load_scalable_texture("resources/heart.png").await,
-            empty_heart: load_scalable_texture("resources/empty_heart.png").await,
-            background: load_scalable_texture("resources/background.png").await,
+    /// Loads the loose images of the asset manifest and packs them into one texture.
+    pub async fn load(assets: &Assets) -> Result<Self, String> {
+        let mut images = Vec::new();
+        for file in &assets.texture_files() {
+            images.push(assets.image(file).await?);
         }
+        let (image, rects) = packer::pack(&images);
+
+        let texture = scalable_texture(&image);
+        let tiles = rects
+            .into_iter()
+            .map(|source| Tile {
+                source,
+                pivot: vec2(0., 0.),
+            })
+            .collect();
+        let atlas = Rc::new(TextureAtlas::from_tiles(texture, tiles));
+
+        // the tiles follow the order of the files
+        let sprite = |tile_index| Sprite {
+            atlas: atlas.clone(),
+            tile_index,
+        };
+        Ok(Self {
+            heart: sprite(0),
+            empty_heart: sprite(1),
+            background: sprite(2),
+        })
     }
 }
 
+/// An image of a packed atlas, drawn from its top left corner.
+#[derive(Clone)]
+pub struct Sprite {
+    atlas: Rc<TextureAtlas>,
+    tile_index: usize,
+}
+
+impl Sprite {
+    pub fn width(&self) -> f32 {
+        self.atlas.tile_size(self.tile_index).x
+    }
+
+    pub fn draw(&self, x: f32, y: f32) {
+        self.atlas
+            .draw_tile(self.tile_index, x, y, DrawOptions::default());
+    }
+}
+
+/// The clips described by the animation manifest.
 pub struct Animations {
-    player_atlas: Rc<TextureAtlas>,
-    enemy_atlas: Rc<TextureAtlas>,
+    clips: BTreeMap<String, Rc<Clip>>,
+    skins: Vec<Skin>,
+    /// Files the animations are loaded from, watched by the hot reload.
+    files: Vec<String>,
 }
 
 impl Animations {
-    pub fn new(textures: &Textures) -> Self {
-        Self {
-            player_atlas: textures.player_atlas.clone(),
-            enemy_atlas: textures.enemy_atlas.clone(),
+    /// Loads the manifest and its atlases, checking that the animations of the game
+    /// are all described.
+    pub async fn load(assets: &Assets) -> Result<Self, String> {
+        let manifest_file = &assets.manifest.animations;
+        let manifest_path = assets.path(manifest_file);
+        let manifest = AnimationManifest::load(assets, manifest_file).await?;
+
+        let mut atlases = BTreeMap::new();
+        // the images of the atlases, kept to recolor them for the skins
+        let mut images = Vec::new();
+        let mut files = vec![manifest_path.clone()];
+        for (name, definition) in &manifest.atlases {
+            let (atlas, image) = definition.load(name, assets).await?;
+            let atlas = Rc::new(atlas);
+            atlases.insert(name.clone(), atlas.clone());
+            images.push((atlas, image));
+            files.push(assets.path(&definition.texture));
+        }
+
+        let mut clips = BTreeMap::new();
+        for (name, file) in &manifest.sheets {
+            let (atlas, sheet_clips, image_file, image) =
+                aseprite::load(name, file, assets).await?;
+
+            if atlases.insert(name.clone(), atlas.clone()).is_some() {
+                return Err(format!("Duplicate atlas {name} in {manifest_path}"));
+            }
+            images.push((atlas, image));
+            files.extend([assets.path(file), assets.path(&image_file)]);
+            clips.extend(sheet_clips);
+        }
+        for (name, definition) in &manifest.clips {
+            let clip = definition.build(name, &atlases)?;
+
+            if clips.insert(name.clone(), Rc::new(clip)).is_some() {
+                return Err(format!("Duplicate clip {name} in {manifest_path}"));
+            }
         }
+
+        for name in Direction::iter().flat_map(required_clips) {
+            if !clips.contains_key(&name) {
+                return Err(format!("Missing clip {name} in {manifest_path}"));
+            }
+        }
+
+        // the skins are made from the clips of the characters, not from other skins
+        let character_clips = clips.clone();
+        let mut skins = Vec::new();
+        for (name, definition) in &manifest.skins {
+            let palette = Palette::load(assets, &definition.palette).await?;
+            files.push(assets.path(&definition.palette));
+
+            let skin_clips = recolor_clips(
+                name,
+                &definition.character,
+                &palette,
+                &character_clips,
+                &images,
+            );
+            if skin_clips.is_empty() {
+                return Err(format!(
+                    "The skin {name} has no clips of {} in {manifest_path}",
+                    definition.character
+                ));
+            }
+            for (clip_name, clip) in skin_clips {
+                if clips.insert(clip_name.clone(), Rc::new(clip)).is_some() {
+                    return Err(format!("Duplicate clip {clip_name} in {manifest_path}"));
+                }
+            }
+            skins.push(Skin {
+                name: name.clone(),
+                character: definition.character.clone(),
+                unlock_score: definition.unlock_score,
+            });
+        }
+
+        Ok(Self {
+            clips,
+            skins,
+            files,
+        })
     }
 
-    /// Returns a player attack animation for the given direction.   
-    pub fn player_attack(&self, direction: Direction) -> AttackAnimation {
-        let indexes = match direction {
-            Direction::Up => 4..8,
-            Direction::Right => 8..12,
-            Direction::Left => 12..16,
-            Direction::Down => 16..20,
-        };
+    /// Returns the clip with the given name, if it is described by the manifest.
+    pub fn clip(&self, name: &str) -> Option<Rc<Clip>> {
+        self.clips.get(name).cloned()
+    }
 
-        AttackAnimation::new(self.player_atlas.clone(), indexes)
+    /// Returns the skins recoloring the clips of the given character.
+    pub fn skins<'a>(&'a self, character: &'a str) -> impl Iterator<Item = &'a Skin> {
+        self.skins
+            .iter()
+            .filter(move |skin| skin.character == character)
     }
 
-    /// Returns an enemy walking animation for the given direction.   
-    pub fn enemy_walking(&self, direction: Direction) -> Animation {
-        let indexes = match direction {
-            Direction::Up => vec![9, 10, 11, 10],
-            Direction::Right => vec![6, 7, 8, 7],
-            Direction::Left => vec![3, 4, 5, 4],
-            Direction::Down => vec![0, 1, 2, 1],
-        };
+    /// Returns the character followed by the names of its skins unlocked by the best
+    /// score.
+    pub fn unlocked_skins<'a>(&'a self, character: &'a str, best_score: u32) -> Vec<&'a str> {
+        let skins = self
+            .skins(character)
+            .filter(|skin| skin.unlock_score <= best_score)
+            .map(|skin| skin.name.as_str());
+
+        iter::once(character).chain(skins).collect()
+    }
+
+    #[cfg_attr(
+        not(all(
+            debug_assertions,
+            not(target_arch = "wasm32"),
+            not(feature = "embedded-assets")
+        )),
+        allow(dead_code)
+    )]
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+}
 
-        Animation::new(self.enemy_atlas.clone(), indexes, 0.1, true)
+#[cfg(test)]
+impl Animations {
+    /// Creates the animations of the given clips, without skins.
+    pub fn from_clips(clips: BTreeMap<String, Rc<Clip>>) -> Self {
+        Self {
+            clips,
+            skins: Vec::new(),
+            files: Vec::new(),
+        }
     }
 }
 
+/// A character drawn with other colors, its clips being named after the skin.
+pub struct Skin {
+    pub name: String,
+    /// Name of the recolored character.
+    pub character: String,
+    /// Best score unlocking the skin, compared by `unlocked_skins`.
+    pub unlock_score: u32,
+}
+
+/// Returns the clips of the character recolored by the palette, named after the skin.
+///
+/// The atlases are recolored from their decoded images, rather than from their
+/// textures which would have to be read back from the graphics card.
+fn recolor_clips(
+    skin: &str,
+    character: &str,
+    palette: &Palette,
+    clips: &BTreeMap<String, Rc<Clip>>,
+    images: &[(Rc<TextureAtlas>, Image)],
+) -> Vec<(String, Clip)> {
+    // each atlas of the character is recolored once, for all its clips
+    let mut atlases: Vec<(Rc<TextureAtlas>, Rc<TextureAtlas>)> = Vec::new();
+    let prefix = format!("{character}_");
+
+    clips
+        .iter()
+        .filter_map(|(name, clip)| Some((name.strip_prefix(&prefix)?, clip)))
+        .map(|(suffix, clip)| {
+            let existing = atlases
+                .iter()
+                .find(|(from, _)| Rc::ptr_eq(from, clip.atlas()))
+                .map(|(_, atlas)| atlas.clone());
+            let atlas = existing.unwrap_or_else(|| {
+                let (_, image) = images
+                    .iter()
+                    .find(|(atlas, _)| Rc::ptr_eq(atlas, clip.atlas()))
+                    .expect("Atlas loaded without its image");
+                let atlas = Rc::new(clip.atlas().recolored(image, palette));
+                atlases.push((clip.atlas().clone(), atlas.clone()));
+                atlas
+            });
+
+            let name = format!("{skin}_{suffix}");
+            (name.clone(), clip.reskinned(name, atlas))
+        })
+        .collect()
+}
+
+/// Returns the names of the clips played by the game for the given direction.
+fn required_clips(direction: Direction) -> [String; 3] {
+    let direction = direction.name();
+
+    [
+        format!("player_idle_{direction}"),
+        format!("player_attack_{direction}"),
+        format!("enemy_walk_{direction}"),
+    ]
+}
+
 pub struct Fonts {
     pub font: Font,
 }
 
 impl Fonts {
-    pub async fn load() -> Self {
-        Self {
-            font: load_ttf_font("resources/Kenney Pixel Square.ttf").await,
-        }
+    pub async fn load(assets: &Assets) -> Result<Self, String> {
+        Ok(Self {
+            font: assets.font(&assets.manifest.font).await?,
+        })
     }
 
     pub const fn sized(&self, font_size: u16) -> TextParams {
@@ -107,8 +307,172 @@ impl Fonts {
 
         draw_text_ex(text, x, y, params);
     }
+
+    /// Draws the text starting at `x`, below `y`.
+    pub fn draw_right(text: &str, x: f32, y: f32, params: TextParams) {
+        let size = measure(text, params);
+
+        let y = y + size.height;
+
+        draw_text_ex(text, x, y, params);
+    }
 }
 
 fn measure(text: &str, params: TextParams) -> TextDimensions {
     measure_text(text, Some(params.font), params.font_size, params.font_scale)
 }
+
+/// A sound effect, played once.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    Slash,
+    Kill,
+    Damage,
+    GameOver,
+}
+
+impl Effect {
+    /// Returns the name of the effect in the asset manifest.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Slash => "slash",
+            Self::Kill => "kill",
+            Self::Damage => "damage",
+            Self::GameOver => "game_over",
+        }
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [Self::Slash, Self::Kill, Self::Damage, Self::GameOver]
+            .iter()
+            .copied()
+    }
+}
+
+/// A music track, played in loop.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Track {
+    Menu,
+    Game,
+}
+
+pub struct Sounds {
+    context: AudioContext,
+    /// Sounds of the effects, in the order of `Effect::iter`.
+    effects: Vec<Sound>,
+    /// Durations of the effects in seconds, in the order of `Effect::iter`.
+    durations: Vec<f64>,
+    menu_music: Sound,
+    game_music: Sound,
+}
+
+impl Sounds {
+    pub async fn load(assets: &Assets) -> Result<Self, String> {
+        let context = AudioContext::new();
+        let manifest = &assets.manifest;
+
+        let mut effects = Vec::new();
+        let mut durations = Vec::new();
+        for effect in Effect::iter() {
+            let file = manifest
+                .effects
+                .get(effect.name())
+                .ok_or_else(|| format!("Missing effect {} in the asset manifest", effect.name()))?;
+            let (sound, duration) = load_sound(&context, assets, file).await?;
+            effects.push(sound);
+            durations.push(duration);
+        }
+
+        Ok(Self {
+            menu_music: load_sound(&context, assets, &manifest.menu_music).await?.0,
+            game_music: load_sound(&context, assets, &manifest.game_music).await?.0,
+            effects,
+            durations,
+            context,
+        })
+    }
+
+    /// Plays the sound effect once at the given volume.
+    pub fn play(&self, effect: Effect, volume: f32) {
+        let params = PlaySoundParams {
+            looped: false,
+            volume,
+        };
+        self.effects[effect as usize].play(&self.context, params);
+    }
+
+    /// Returns the duration of the sound effect in seconds.
+    pub fn duration(&self, effect: Effect) -> f64 {
+        self.durations[effect as usize]
+    }
+
+    /// Plays the music track in loop, starting at the given volume.
+    pub fn play_looped(&self, track: Track, volume: f32) -> Playback {
+        let sound = match track {
+            Track::Menu => &self.menu_music,
+            Track::Game => &self.game_music,
+        };
+        let params = PlaySoundParams {
+            looped: true,
+            volume,
+        };
+        sound.play(&self.context, params)
+    }
+
+    pub fn set_volume(&self, playback: &Playback, volume: f32) {
+        playback.set_volume(&self.context, volume);
+    }
+
+    pub fn stop(&self, playback: Playback) {
+        playback.stop(&self.context);
+    }
+}
+
+/// Loads the WAV file, returning the sound along with its duration in seconds.
+async fn load_sound(
+    context: &AudioContext,
+    assets: &Assets,
+    file: &str,
+) -> Result<(Sound, f64), String> {
+    let bytes = assets.read(file).await?;
+    let duration =
+        wav_duration(&bytes).ok_or_else(|| format!("Invalid WAV file {}", assets.path(file)))?;
+    let sound = Sound::load(context, &bytes);
+
+    #[cfg(target_arch = "wasm32")]
+    while !sound.is_loaded() {
+        next_frame().await; // the browser decodes the sounds asynchronously
+    }
+    Ok((sound, duration))
+}
+
+/// Returns the duration of a WAV file, from the byte rate of its `fmt ` chunk and the
+/// size of its `data` chunk, or `None` if the file is not a valid WAV file.
+fn wav_duration(bytes: &[u8]) -> Option<f64> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    let mut byte_rate = None;
+    let mut chunks = &bytes[12..];
+    while chunks.len() >= 8 {
+        let size = read_u32(chunks, 4)? as usize;
+        let body = &chunks[8..];
+
+        match &chunks[0..4] {
+            b"fmt " => byte_rate = Some(read_u32(body, 8)?).filter(|rate| *rate > 0),
+            // a truncated file only plays its remaining samples
+            b"data" => return Some(size.min(body.len()) as f64 / f64::from(byte_rate?)),
+            _ => {}
+        }
+        // the chunks are padded to an even size
+        chunks = body.get(size + size % 2..).unwrap_or_default();
+    }
+    None
+}
+
+/// Reads a little-endian `u32` at the given offset.
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,80 @@
+use crate::{
+    camera::CameraEffects,
+    direction::Direction,
+    enemy::Enemy,
+    life_bar::LifeBar,
+    particles::Particles,
+    player::Player,
+    resources::{Animations, Textures},
+    timers::{game_time, Cooldown, InputBuffer, Timer},
+    tuning::Tuning,
+};
+
+/// The state of a run, from the start of the game until the player has no more lives.
+pub struct Run {
+    pub score: u32,
+    pub life_bar: LifeBar,
+    pub player: Player,
+    pub enemies: Vec<Enemy>,
+    pub particles: Particles,
+    pub camera: CameraEffects,
+    pub attack_cooldown: Cooldown,
+    pub attack_buffer: InputBuffer<Direction>,
+    pub enemy_spawner: Timer,
+    /// Game time at which the run started.
+    pub started_at: f64,
+    pub paused: bool,
+    /// Whether the player is invulnerable, set from the console.
+    pub god_mode: bool,
+    /// Speed of the game time, changed from the console.
+    pub time_scale: f32,
+    /// Whether a cheat was used from the console, the score then not counting as the
+    /// best score.
+    pub cheated: bool,
+}
+
+impl Run {
+    /// Starts a run with the player drawn with the given skin.
+    pub fn new(
+        textures: &Textures,
+        animations: &Animations,
+        tuning: &Tuning,
+        player_skin: &str,
+    ) -> Self {
+        Self {
+            score: 0,
+            life_bar: LifeBar::new(tuning.lives, textures),
+            player: Player::new(12., 12., player_skin, animations),
+            enemies: Vec::new(),
+            particles: Particles::default(),
+            camera: CameraEffects::default(),
+            attack_cooldown: Cooldown::from_seconds(tuning.attack_cooldown),
+            attack_buffer: InputBuffer::from_seconds(tuning.attack_buffer),
+            enemy_spawner: Timer::from_seconds(tuning.initial_spawn_delay),
+            started_at: game_time(),
+            paused: false,
+            god_mode: false,
+            time_scale: 1.,
+            cheated: false,
+        }
+    }
+
+    /// Applies the tuning changed during the run.
+    ///
+    /// The enemy speed is read at each frame, while the lives and the spawn delay only
+    /// apply to the next runs.
+    pub const fn apply_tuning(&mut self, tuning: &Tuning) {
+        self.attack_cooldown.set_duration(tuning.attack_cooldown);
+        self.attack_buffer.set_duration(tuning.attack_buffer);
+    }
+
+    /// Replaces the textures and the animations of the entities with the reloaded ones.
+    pub fn refresh_textures(&mut self, textures: &Textures, animations: &Animations) {
+        self.life_bar.refresh_textures(textures);
+        self.player.refresh_animations(animations);
+
+        for enemy in &mut self.enemies {
+            enemy.animator.reload(animations);
+        }
+    }
+}
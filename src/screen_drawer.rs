@@ -1,14 +1,56 @@
 //! The screen drawer for scaling things drawn to the game into the screen size.
 
 use macroquad::prelude::*;
+use nanoserde::{DeRon, SerRon};
+
+/// How the game is scaled to the screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, SerRon, DeRon)]
+pub enum ScalingMode {
+    /// Fills the whole screen, distorting the pixels if its aspect ratio differs.
+    Stretch,
+    /// Fills as much of the screen as possible while keeping the aspect ratio.
+    #[default]
+    Fit,
+    /// Scales by the largest whole factor that fits, keeping the pixels crisp and square.
+    Integer,
+}
+
+impl ScalingMode {
+    /// Returns the following mode, wrapping around.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Stretch => Self::Fit,
+            Self::Fit => Self::Integer,
+            Self::Integer => Self::Stretch,
+        }
+    }
+
+    /// Returns the preceding mode, wrapping around.
+    pub const fn previous(self) -> Self {
+        match self {
+            Self::Stretch => Self::Integer,
+            Self::Fit => Self::Stretch,
+            Self::Integer => Self::Fit,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Stretch => "Stretch",
+            Self::Fit => "Fit",
+            Self::Integer => "Integer",
+        }
+    }
+}
 
 pub struct ScreenDrawer {
     render_target: RenderTarget,
     game_size: Vec2,
+    pub scaling: ScalingMode,
 }
 
 impl ScreenDrawer {
-    pub fn new(game_width: f32, game_height: f32) -> Self {
+    pub fn new(game_width: f32, game_height: f32, scaling: ScalingMode) -> Self {
         #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
         let render_target = render_target(game_width as u32, game_height as u32);
         set_texture_filter(render_target.texture, FilterMode::Nearest);
@@ -16,14 +58,20 @@ impl ScreenDrawer {
         Self {
             render_target,
             game_size: vec2(game_width, game_height),
+            scaling,
         }
     }
 
     /// Executes the given `draw` function and scale the drawn things to the screen size.
     pub fn draw_scaled(&self, draw: impl FnOnce()) {
+        self.draw_scaled_with_view(Vec2::zero(), 1., draw);
+    }
+
+    /// Same as [`Self::draw_scaled`], with the camera moved by `offset` and scaled by `zoom`.
+    pub fn draw_scaled_with_view(&self, offset: Vec2, zoom: f32, draw: impl FnOnce()) {
         set_camera(Camera2D {
-            zoom: 1.0 / self.game_size * 2.0,
-            target: (self.game_size * 0.5).floor(),
+            zoom: 1.0 / self.game_size * 2.0 * zoom,
+            target: (self.game_size * 0.5).floor() + offset,
             render_target: Some(self.render_target),
             ..Camera2D::default()
         });
@@ -31,22 +79,56 @@ impl ScreenDrawer {
         draw();
 
         set_default_camera();
+        clear_background(BLACK);
+
+        let Rect { x, y, w, h } = self.destination();
         draw_texture_ex(
             self.render_target.texture,
-            0.,
-            0.,
+            x,
+            y,
             WHITE,
             DrawTextureParams {
-                dest_size: Some(vec2(screen_width(), screen_height())),
+                dest_size: Some(vec2(w, h)),
                 ..DrawTextureParams::default()
             },
         );
     }
+
+    /// Converts a position on the screen to a position in the game.
+    pub fn to_game(&self, screen_position: Vec2) -> Vec2 {
+        let Rect { x, y, w, h } = self.destination();
+        (screen_position - vec2(x, y)) / vec2(w, h) * self.game_size
+    }
+
+    /// Returns the area of the screen in which the game is drawn.
+    fn destination(&self) -> Rect {
+        let screen_size = vec2(screen_width(), screen_height());
+        let fit_scale = (screen_size / self.game_size).min_element();
+
+        let size = match self.scaling {
+            ScalingMode::Stretch => return Rect::new(0., 0., screen_size.x, screen_size.y),
+            ScalingMode::Fit => (self.game_size * fit_scale).floor(),
+            ScalingMode::Integer => self.game_size * fit_scale.floor().max(1.),
+        };
+        let position = ((screen_size - size) / 2.).floor(); // centered
+
+        Rect::new(position.x, position.y, size.x, size.y)
+    }
 }
 
-/// Loads a texture that will be scaled to the screen.
-pub async fn load_scalable_texture(file_name: &str) -> Texture2D {
-    let texture = load_texture(file_name).await;
-    set_texture_filter(texture, FilterMode::Nearest); // scale pixels without smoothing
-    texture
+/// Whether the window switches to fullscreen while the game runs. miniquad only does it
+/// on Windows and in the browser, the other platforms applying the setting on restart.
+pub const FULLSCREEN_AT_RUNTIME: bool = !cfg!(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+));
+
+/// Switches the window to fullscreen or back, where the platform supports it at runtime.
+pub fn set_fullscreen(fullscreen: bool) {
+    // SAFETY: only called from the main thread, between two frames.
+    unsafe { get_internal_gl() }
+        .quad_context
+        .set_fullscreen(fullscreen);
 }
@@ -0,0 +1,78 @@
+//! The settings of the player, saved between launches of the game.
+
+use nanoserde::{DeRon, SerRon};
+
+use crate::{
+    input::{Bindings, ControlScheme},
+    mixer::Volumes,
+    screen_drawer::ScalingMode,
+};
+
+/// File in which the settings are saved.
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_FILE: &str = "settings.ron";
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, SerRon, DeRon)]
+pub struct Settings {
+    #[nserde(default)]
+    pub bindings: Bindings,
+    #[nserde(default)]
+    pub control_scheme: ControlScheme,
+    #[nserde(default)]
+    pub volumes: Volumes,
+    #[nserde(default)]
+    pub fullscreen: bool,
+    #[nserde(default)]
+    pub scaling: ScalingMode,
+    /// Whether the camera shakes, zooms and freezes on the hits, disabled for motion
+    /// sensitivity.
+    #[nserde(default = "true")]
+    pub screen_shake: bool,
+    #[nserde(default)]
+    pub show_hit_boxes: bool,
+    /// Skin of the player, the default one when empty.
+    #[nserde(default)]
+    pub skin: String,
+    /// Best score of all the runs, unlocking the skins.
+    #[nserde(default)]
+    pub best_score: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: Bindings::default(),
+            control_scheme: ControlScheme::default(),
+            volumes: Volumes::default(),
+            fullscreen: false,
+            scaling: ScalingMode::default(),
+            screen_shake: true,
+            show_hit_boxes: false,
+            skin: String::new(),
+            best_score: 0,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the saved settings, or the default ones if they cannot be read.
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(content) = std::fs::read_to_string(SETTINGS_FILE) {
+            match Self::deserialize_ron(&content) {
+                Ok(settings) => return settings,
+                Err(err) => eprintln!("Invalid settings in {SETTINGS_FILE}: {err}"),
+            }
+        }
+        Self::default()
+    }
+
+    /// Saves the settings, which is not supported in the browser.
+    pub fn save(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = std::fs::write(SETTINGS_FILE, self.serialize_ron()) {
+            eprintln!("Cannot save the settings in {SETTINGS_FILE}: {err}");
+        }
+    }
+}
@@ -1,5 +1,49 @@
+use std::cell::Cell;
+
 use macroquad::prelude::*;
 
+thread_local! {
+    /// Time elapsed in the game, which does not advance while the game is paused.
+    static GAME_TIME: Cell<f64> = const { Cell::new(0.) };
+    /// Duration of the last frame of the game.
+    static GAME_FRAME_TIME: Cell<f32> = const { Cell::new(0.) };
+}
+
+/// Returns the duration of the last frame multiplied by the time scale, or zero if the
+/// game is paused.
+pub fn scaled_frame_time(paused: bool, time_scale: f32) -> f32 {
+    if paused {
+        0.
+    } else {
+        get_frame_time() * time_scale
+    }
+}
+
+/// Advances the game time by the duration of the last frame multiplied by the time scale,
+/// unless the game is paused.
+pub fn advance_game_time(paused: bool, time_scale: f32) {
+    let frame_time = scaled_frame_time(paused, time_scale);
+
+    GAME_FRAME_TIME.with(|time| time.set(frame_time));
+    GAME_TIME.with(|time| time.set(time.get() + f64::from(frame_time)));
+}
+
+/// Advances the game time by the given duration, without any frame.
+#[cfg(test)]
+pub fn skip_game_time(seconds: f64) {
+    GAME_TIME.with(|time| time.set(time.get() + seconds));
+}
+
+/// Returns the time elapsed in the game.
+pub fn game_time() -> f64 {
+    GAME_TIME.with(Cell::get)
+}
+
+/// Returns the duration of the last frame of the game.
+pub fn game_frame_time() -> f32 {
+    GAME_FRAME_TIME.with(Cell::get)
+}
+
 pub struct Timer {
     time: f64,
     pub delay: f64,
@@ -8,14 +52,14 @@ pub struct Timer {
 impl Timer {
     pub fn from_seconds(delay: f64) -> Self {
         Self {
-            time: get_time(),
+            time: game_time(),
             delay,
         }
     }
 
     /// Updates the tracked time and returns true if the delay has just been reached.
     pub fn tick_and_finished(&mut self) -> bool {
-        let current_time = get_time();
+        let current_time = game_time();
         let finished = current_time - self.time > self.delay;
 
         if finished {
@@ -25,7 +69,17 @@ impl Timer {
     }
 
     pub fn restart(&mut self) {
-        self.time = get_time()
+        self.time = game_time();
+    }
+
+    /// Returns the time left before the delay is reached.
+    pub fn remaining(&self) -> f64 {
+        (self.delay - (game_time() - self.time)).max(0.)
+    }
+
+    /// Returns the part of the delay elapsed since the last restart, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        ((game_time() - self.time) / self.delay).min(1.) as f32
     }
 }
 
@@ -45,18 +99,74 @@ impl Cooldown {
         }
     }
 
+    /// Changes the duration of the next cooldowns.
+    pub const fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+
     /// Starts the cooldown, making it unavailable for the given duration.
     pub fn start(&mut self) {
-        self.available_time = get_time() + self.duration;
+        self.available_time = game_time() + self.duration;
     }
 
     /// Resets the cooldown, making it available again.
-    pub fn reset(&mut self) {
+    pub const fn reset(&mut self) {
         self.available_time = 0.0;
     }
 
+    /// Returns the time left before the cooldown is available.
+    pub fn remaining(&self) -> f64 {
+        (self.available_time - game_time()).max(0.)
+    }
+
     /// Returns true if the cooldown is available.
     pub fn available(&self) -> bool {
-        self.available_time <= get_time()
+        self.available_time <= game_time()
+    }
+}
+
+/// Keeps the last pressed input for a short duration so it can be executed
+/// later, when the action becomes available.
+pub struct InputBuffer<T> {
+    /// The buffered input with the time it was pressed.
+    buffered: Option<(T, f64)>,
+    /// How long an input stays buffered.
+    duration: f64,
+}
+
+impl<T> InputBuffer<T> {
+    /// Creates an empty buffer keeping the inputs for the given duration.
+    pub const fn from_seconds(duration: f64) -> Self {
+        Self {
+            buffered: None,
+            duration,
+        }
+    }
+
+    /// Changes how long the next inputs stay buffered.
+    pub const fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+
+    /// Buffers the given input, replacing the previous one.
+    pub fn press(&mut self, input: T) {
+        self.buffered = Some((input, game_time()));
+    }
+
+    /// Replaces the buffered input, if any, without extending its duration.
+    pub fn replace(&mut self, input: T) {
+        if let Some((buffered, _)) = &mut self.buffered {
+            *buffered = input;
+        }
+    }
+
+    /// Takes the buffered input if it has not expired yet.
+    pub fn take(&mut self) -> Option<T> {
+        let duration = self.duration;
+
+        self.buffered
+            .take()
+            .filter(|(_, time)| game_time() - time <= duration)
+            .map(|(input, _)| input)
     }
 }
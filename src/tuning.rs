@@ -0,0 +1,52 @@
+//! The values balancing the gameplay, read from a file so they can be adjusted
+//! without rebuilding the game.
+
+use nanoserde::DeRon;
+
+use crate::assets::Assets;
+
+#[derive(Clone, DeRon)]
+pub struct Tuning {
+    /// Movement speed of enemies.
+    pub enemy_speed: f32,
+    /// Initial delay between each enemy spawn.
+    pub initial_spawn_delay: f64,
+    /// Duration of the cooldown between attacks.
+    pub attack_cooldown: f64,
+    /// Duration during which an attack press is kept until the cooldown is available.
+    pub attack_buffer: f64,
+    /// Initial amount of life the player has.
+    pub lives: u32,
+}
+
+/// The tuning shipped with the game, which is also the one used when the tuning file
+/// cannot be read, so that its values are only written there.
+const DEFAULT_TUNING: &str = include_str!("../resources/tuning.ron");
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self::deserialize_ron(DEFAULT_TUNING).expect("Invalid default tuning")
+    }
+}
+
+impl Tuning {
+    /// Loads the tuning file, or the default tuning if it cannot be read.
+    pub fn load(assets: &Assets) -> Self {
+        Self::read(assets).unwrap_or_default()
+    }
+
+    /// Reads the tuning file, returning `None` when it is missing or invalid.
+    ///
+    /// The browser only reads the embedded file, using the default tuning otherwise.
+    pub fn read(assets: &Assets) -> Option<Self> {
+        let file = &assets.manifest.tuning;
+        let content = assets
+            .read_string_now(file)
+            .map_err(|err| eprintln!("Cannot read the tuning: {err}"))
+            .ok()?;
+
+        Self::deserialize_ron(&content)
+            .map_err(|err| eprintln!("Invalid tuning in {}: {err}", assets.path(file)))
+            .ok()
+    }
+}
@@ -0,0 +1,26 @@
+// Plugin giving access to the browser gamepads, read by the `gamepad` module of the game.
+miniquad_add_plugin({
+    name: "gamepad",
+    version: "0.1.0",
+    register_plugin: function (importObject) {
+        function gamepad(index) {
+            return navigator.getGamepads ? navigator.getGamepads()[index] : null;
+        }
+
+        importObject.env.gamepad_count = function () {
+            return navigator.getGamepads ? navigator.getGamepads().length : 0;
+        };
+        importObject.env.gamepad_connected = function (index) {
+            var pad = gamepad(index);
+            return pad != null && pad.connected && pad.mapping == "standard";
+        };
+        importObject.env.gamepad_button = function (index, button) {
+            var pad = gamepad(index);
+            return pad != null && button < pad.buttons.length && pad.buttons[button].pressed;
+        };
+        importObject.env.gamepad_axis = function (index, axis) {
+            var pad = gamepad(index);
+            return pad != null && axis < pad.axes.length ? pad.axes[axis] : 0;
+        };
+    }
+});